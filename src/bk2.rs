@@ -0,0 +1,229 @@
+use crate::{
+    command::Command, input::Input, movie::Movie, movie_error::MovieError, movie_frame::MovieFrame,
+    movie_metadata::MovieMetadata,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path,
+};
+use zip::ZipArchive;
+
+impl Movie {
+    const BK2_NES_CORES: [&'static str; 3] = ["NesHawk", "QuickNes", "SubNESHawk"];
+
+    /// Reads a movie from a BK2 archive, as used by BizHawk.
+    ///
+    /// Movies recorded on a PAL console, with a ROM other than NTSC NES
+    /// Tetris, with a core which does not emulate an NES, with devices other
+    /// than standard controllers or starting from a savestate are rejected
+    /// with a [`MovieError`]. As BK2 files do not contain FCEUX's ROM checksum,
+    /// the resulting `metadata` lists that of NES Tetris once the ROM's SHA-1
+    /// hash has been verified.
+    pub fn from_bk2(path: impl AsRef<Path>) -> Result<Self, MovieError> {
+        Self::from_bk2_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a movie in the BK2 format from an arbitrary reader, such as
+    /// a [`Cursor`](std::io::Cursor) over an in-memory archive.
+    ///
+    /// See [`Movie::from_bk2`] for details.
+    pub fn from_bk2_reader(reader: impl Read + Seek) -> Result<Self, MovieError> {
+        let mut archive = ZipArchive::new(reader)?;
+
+        let mut header = String::new();
+        archive.by_name("Header.txt")?.read_to_string(&mut header)?;
+        let metadata = Self::parse_bk2_header(&header)?;
+
+        let mut input_log = String::new();
+        archive
+            .by_name("Input Log.txt")?
+            .read_to_string(&mut input_log)?;
+        let frames = Self::parse_bk2_input_log(&input_log)?;
+
+        Ok(Movie::from_frames(metadata, frames))
+    }
+
+    fn parse_bk2_header(header: &str) -> Result<MovieMetadata, MovieError> {
+        fn parse_flag(value: &str) -> bool {
+            value.eq_ignore_ascii_case("true") || value == "1"
+        }
+
+        let mut metadata = MovieMetadata::new();
+        let mut sha1 = None;
+        let mut core = None;
+        for (line_index, line) in header.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "rerecordCount" => {
+                    metadata.rerecord_count =
+                        value.parse().map_err(|_| MovieError::MalformedHeader {
+                            line: line_index + 1,
+                            content: line.into(),
+                        })?;
+                }
+                "PAL" => metadata.pal = parse_flag(value),
+                "StartsFromSavestate" | "StartsFromSaveRam" if parse_flag(value) => {
+                    return Err(MovieError::UnsupportedFeature {
+                        feature: "starting from a savestate".into(),
+                    })
+                }
+                "GameName" => metadata.rom_filename = value.into(),
+                "Author" => metadata.comments.push(format!("author {value}")),
+                "SHA1" => sha1 = Some(value.to_ascii_uppercase()),
+                "Core" => core = Some(value.to_owned()),
+                _ => metadata.other_keys.push((key.into(), value.into())),
+            }
+        }
+
+        if metadata.pal {
+            return Err(MovieError::PalRegion);
+        }
+        match sha1 {
+            Some(sha1) if sha1 == MovieMetadata::TETRIS_ROM_SHA1 => (),
+            sha1 => {
+                return Err(MovieError::RomMismatch {
+                    checksum: sha1.unwrap_or_default(),
+                })
+            }
+        }
+        match core {
+            Some(core) if Self::BK2_NES_CORES.contains(&core.as_str()) => (),
+            core => {
+                return Err(MovieError::UnsupportedCore {
+                    core: core.unwrap_or_default(),
+                })
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    fn parse_bk2_input_log(input_log: &str) -> Result<Vec<MovieFrame>, MovieError> {
+        // BizHawk's NES log key, used if the input log does not contain its own
+        const DEFAULT_LOG_KEY: &str = concat!(
+            "#Reset|Power|",
+            "#P1 Up|P1 Down|P1 Left|P1 Right|P1 Start|P1 Select|P1 B|P1 A|",
+            "#P2 Up|P2 Down|P2 Left|P2 Right|P2 Start|P2 Select|P2 B|P2 A|",
+        );
+
+        #[derive(Clone, Copy)]
+        enum Button {
+            Command(Command),
+            Controller(usize, Input), // zero-based controller index
+            Other,
+        }
+
+        fn parse_button(name: &str) -> Result<Button, MovieError> {
+            match name {
+                "Reset" => return Ok(Button::Command(Command::SoftReset)),
+                "Power" => return Ok(Button::Command(Command::PowerCycle)),
+                _ => (),
+            }
+
+            let player_button = name.strip_prefix('P').and_then(|n| n.split_once(' '));
+            let Some((player, button)) = player_button else {
+                return Ok(Button::Other);
+            };
+            let Ok(controller @ 1..=4) = player.parse::<usize>() else {
+                return Ok(Button::Other);
+            };
+
+            let input = match button {
+                "Up" => Input::Up,
+                "Down" => Input::Down,
+                "Left" => Input::Left,
+                "Right" => Input::Right,
+                "Start" => Input::Start,
+                "Select" => Input::Select,
+                "B" => Input::B,
+                "A" => Input::A,
+                // with a Four Score, controllers 3 and 4 share the ports of
+                // controllers 1 and 2
+                _ => {
+                    return Err(MovieError::UnsupportedDevice {
+                        port: (controller - 1) % 2,
+                        device: name.into(),
+                    })
+                }
+            };
+            Ok(Button::Controller(controller - 1, input))
+        }
+
+        fn parse_log_key(log_key: &str) -> Result<Vec<Vec<Button>>, MovieError> {
+            log_key
+                .split('#')
+                .filter(|group| !group.is_empty())
+                .map(|group| {
+                    group
+                        .split('|')
+                        .filter(|b| !b.is_empty())
+                        .map(parse_button)
+                        .collect()
+                })
+                .collect()
+        }
+
+        let mut log_key = parse_log_key(DEFAULT_LOG_KEY)?;
+        let mut frames = Vec::new();
+        for (line_index, line) in input_log.lines().enumerate() {
+            if let Some(custom_log_key) = line.strip_prefix("LogKey:") {
+                log_key = parse_log_key(custom_log_key)?;
+                continue;
+            }
+            if !line.starts_with('|') {
+                continue; // section markers such as [Input] and [/Input]
+            }
+
+            let groups = line
+                .trim_start_matches('|')
+                .trim_end_matches('|')
+                .split('|')
+                .collect::<Vec<_>>();
+            if groups.len() != log_key.len() {
+                return Err(MovieError::BadInputLine {
+                    line: line_index + 1,
+                    content: line.into(),
+                });
+            }
+
+            let mut controller_inputs = [Input::empty(); 4];
+            let mut command = Command::empty();
+            for (group, buttons) in groups.iter().zip(&log_key) {
+                let width = group.chars().count();
+                if width != buttons.len() {
+                    return Err(MovieError::WrongFieldWidth {
+                        line: Some(line_index + 1),
+                        expected: buttons.len(),
+                        found: width,
+                    });
+                }
+
+                for (character, button) in group.chars().zip(buttons) {
+                    if character == '.' || character == ' ' {
+                        continue;
+                    }
+
+                    match *button {
+                        Button::Command(button_command) => command |= button_command,
+                        Button::Controller(index, input) => controller_inputs[index] |= input,
+                        Button::Other => (),
+                    }
+                }
+            }
+
+            let [input, other_inputs @ ..] = controller_inputs;
+            frames.push(MovieFrame {
+                input,
+                other_inputs,
+                command,
+            });
+        }
+
+        Ok(frames)
+    }
+}
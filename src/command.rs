@@ -0,0 +1,21 @@
+use bitflags::bitflags;
+
+bitflags! {
+    /// Console-level events recorded alongside the inputs of a movie frame.
+    ///
+    /// The bit values match those of the command field of FM2 input log lines.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct Command: u8 {
+        const SoftReset = 0x01;
+        const PowerCycle = 0x02;
+        const FdsDiskInsert = 0x04;
+        const FdsDiskSelect = 0x08;
+        const VsInsertCoin = 0x10;
+    }
+}
+
+impl Default for Command {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
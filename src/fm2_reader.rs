@@ -0,0 +1,265 @@
+use crate::{
+    command::Command, input::Input, movie_error::MovieError, movie_frame::MovieFrame,
+    movie_metadata::MovieMetadata,
+};
+use std::io::BufRead;
+
+/// A streaming reader of movies in the FM2 format, as used by FCEUX.
+///
+/// The header is read upon creation, after which the reader iterates over the
+/// movie's frames one line at a time:
+/// ```no_run
+/// use meta_nestris::{Fm2Reader, State};
+/// use std::{fs::File, io::BufReader};
+///
+/// let file = File::open("inputs.fm2").expect("File not found.");
+/// let reader = Fm2Reader::new(BufReader::new(file)).expect("Invalid header.");
+///
+/// let mut state = State::new();
+/// for frame in reader {
+///     let frame = frame.expect("Invalid input line.");
+///     state.apply_command(frame.command);
+///     state.step(frame.input);
+/// }
+/// ```
+///
+/// Iteration ends after the first error.
+pub struct Fm2Reader<R: BufRead> {
+    reader: R,
+    metadata: MovieMetadata,
+    line: String,
+    line_number: usize,
+    line_pending: bool, // the first input log line is read along with the header
+    finished: bool,
+}
+
+impl<R: BufRead> Fm2Reader<R> {
+    /// Creates an `Fm2Reader`, reading the movie's header.
+    ///
+    /// Movies recorded on a PAL console, with a ROM other than NTSC NES
+    /// Tetris, with devices other than standard controllers or relying on
+    /// unsupported features are rejected with a [`MovieError`].
+    pub fn new(reader: R) -> Result<Self, MovieError> {
+        let mut fm2_reader = Self {
+            reader,
+            // a movie lacking a ROM checksum is rejected
+            metadata: MovieMetadata {
+                rom_checksum: None,
+                ..MovieMetadata::new()
+            },
+            line: String::new(),
+            line_number: 0,
+            line_pending: false,
+            finished: false,
+        };
+
+        while fm2_reader.read_line()? {
+            if fm2_reader.line.starts_with('|') {
+                fm2_reader.line_pending = true;
+                break;
+            }
+
+            fm2_reader.parse_header_line()?;
+        }
+
+        let metadata = &fm2_reader.metadata;
+        if metadata.pal {
+            return Err(MovieError::PalRegion);
+        }
+        match &metadata.rom_checksum {
+            Some(checksum) if checksum == MovieMetadata::TETRIS_ROM_CHECKSUM => (),
+            checksum => {
+                return Err(MovieError::RomMismatch {
+                    checksum: checksum.clone().unwrap_or_default(),
+                })
+            }
+        }
+
+        for (port, &device) in metadata.ports.iter().enumerate() {
+            let supported = match port {
+                // with a Four Score, both controller ports hold two standard
+                // controllers each, regardless of the declared device types
+                0 | 1 => {
+                    metadata.fourscore
+                        || device == MovieMetadata::DEVICE_NONE
+                        || device == MovieMetadata::DEVICE_GAMEPAD
+                }
+                _ => device == MovieMetadata::DEVICE_NONE,
+            };
+            if !supported {
+                return Err(MovieError::UnsupportedDevice {
+                    port,
+                    device: format!("device type {device}"),
+                });
+            }
+        }
+
+        Ok(fm2_reader)
+    }
+
+    #[must_use]
+    pub fn metadata(&self) -> &MovieMetadata {
+        &self.metadata
+    }
+
+    // reads the next line into the buffer, returning false at the end of input
+    fn read_line(&mut self) -> Result<bool, MovieError> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(false);
+        }
+
+        self.line_number += 1;
+        let trimmed_length = self.line.trim_end_matches(['\r', '\n']).len();
+        self.line.truncate(trimmed_length);
+        Ok(true)
+    }
+
+    fn parse_header_line(&mut self) -> Result<(), MovieError> {
+        if self.line.trim().is_empty() {
+            return Ok(());
+        }
+
+        let (key, value) = self.line.split_once(' ').unwrap_or((&self.line, ""));
+        let unsupported_feature = match key {
+            "binary" if value != "0" => Some("binary input log"),
+            "savestate" => Some("starting from a savestate"),
+            "FDS" if value != "0" => Some("Famicom Disk System"),
+            _ => None,
+        };
+        if let Some(feature) = unsupported_feature {
+            return Err(MovieError::UnsupportedFeature {
+                feature: feature.into(),
+            });
+        }
+
+        if Self::parse_header_entry(&mut self.metadata, key, value).is_none() {
+            return Err(MovieError::MalformedHeader {
+                line: self.line_number,
+                content: self.line.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn parse_header_entry(metadata: &mut MovieMetadata, key: &str, value: &str) -> Option<()> {
+        fn parse_flag(value: &str) -> Option<bool> {
+            match value {
+                "0" => Some(false),
+                "1" => Some(true),
+                _ => None,
+            }
+        }
+
+        match key {
+            "version" => metadata.version = value.parse().ok()?,
+            "emuVersion" => metadata.emu_version = value.parse().ok()?,
+            "rerecordCount" => metadata.rerecord_count = value.parse().ok()?,
+            "palFlag" => metadata.pal = parse_flag(value)?,
+            "romFilename" => metadata.rom_filename = value.into(),
+            "romChecksum" => metadata.rom_checksum = Some(value.into()),
+            "guid" => metadata.guid = value.into(),
+            "fourscore" => metadata.fourscore = parse_flag(value)?,
+            "microphone" => metadata.microphone = parse_flag(value)?,
+            "port0" => metadata.ports[0] = value.parse().ok()?,
+            "port1" => metadata.ports[1] = value.parse().ok()?,
+            "port2" => metadata.ports[2] = value.parse().ok()?,
+            "FDS" => metadata.fds = parse_flag(value)?,
+            "NewPPU" => metadata.new_ppu = parse_flag(value)?,
+            "RAMInitOption" => metadata.ram_init_option = value.parse().ok()?,
+            "RAMInitSeed" => metadata.ram_init_seed = value.parse().ok()?,
+            "comment" => metadata.comments.push(value.into()),
+            _ => metadata.other_keys.push((key.into(), value.into())),
+        }
+
+        Some(())
+    }
+
+    fn parse_input_line(&self) -> Result<MovieFrame, MovieError> {
+        let bad_input_line = || MovieError::BadInputLine {
+            line: self.line_number,
+            content: self.line.clone(),
+        };
+
+        // a command field, followed by a field per controller and one for the
+        // expansion port
+        let controller_count = if self.metadata.fourscore { 4 } else { 2 };
+        let fields = self
+            .line
+            .strip_prefix('|')
+            .and_then(|line| line.strip_suffix('|'))
+            .ok_or_else(bad_input_line)?
+            .split('|')
+            .collect::<Vec<_>>();
+        if fields.len() != controller_count + 2 {
+            return Err(bad_input_line());
+        }
+
+        let command = fields[0].parse().map_err(|_| bad_input_line())?;
+        let mut inputs = [Input::empty(); 4];
+        for (index, field) in fields[1..].iter().enumerate() {
+            let connected = index < controller_count
+                && (self.metadata.fourscore
+                    || self.metadata.ports[index] == MovieMetadata::DEVICE_GAMEPAD);
+            if connected {
+                inputs[index] = self.parse_controller_field(field)?;
+            } else if !field.is_empty() {
+                return Err(MovieError::WrongFieldWidth {
+                    line: Some(self.line_number),
+                    expected: 0,
+                    found: field.chars().count(),
+                });
+            }
+        }
+
+        Ok(MovieFrame {
+            input: inputs[0],
+            other_inputs: [inputs[1], inputs[2], inputs[3]],
+            command: Command::from_bits_retain(command),
+        })
+    }
+
+    fn parse_controller_field(&self, field: &str) -> Result<Input, MovieError> {
+        Input::from_fm2_string(field).map_err(|error| match error {
+            MovieError::WrongFieldWidth {
+                expected, found, ..
+            } => MovieError::WrongFieldWidth {
+                line: Some(self.line_number),
+                expected,
+                found,
+            },
+            error => error,
+        })
+    }
+}
+
+impl<R: BufRead> Iterator for Fm2Reader<R> {
+    type Item = Result<MovieFrame, MovieError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        if self.line_pending {
+            self.line_pending = false;
+        } else {
+            match self.read_line() {
+                Ok(true) => (),
+                Ok(false) => {
+                    self.finished = true;
+                    return None;
+                }
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        let frame = self.parse_input_line();
+        self.finished = frame.is_err();
+        Some(frame)
+    }
+}
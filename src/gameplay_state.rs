@@ -6,15 +6,21 @@ use crate::{
 };
 use bitvec::prelude::*;
 
+// holding A, B, Select and Start returns to the copyright screen, which is
+// handled by State::step
+pub(crate) const RESET_COMBO: Input = Input::from_bits_retain(0xf0);
+
 /// A de facto gameplay state; i.e. a state where the playfield is present.
 ///
 /// The `MODIFIER` const generic specifies game modifiers - see [`Modifier`] for
 /// supported modifiers.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameplayState<const MODIFIER: Modifier> {
     // each field is listed with its equivalent from the base game
-    pub dead: bool,   // $68 == #10, once true never changes back to false
-    pub paused: bool, // true if execution is in loop at $a3c4
+    pub dead: bool,     // $68 == #10, once true never changes back to false
+    pub finished: bool, // B-type goal check at $68 == #6 passed, once true never changes back
+    pub paused: bool,   // true if execution is in loop at $a3c4
     pub game_mode_state: GameModeState, // $a7
     pub play_state: PlayState, // $68
     pub checked_row_offset: u8, // $aa in routine at $99a2, but counts up instead of down
@@ -31,13 +37,15 @@ pub struct GameplayState<const MODIFIER: Modifier> {
     pub drop_autorepeat: i8, // $6e
     pub shift_autorepeat: u8, // $66
     pub game_type: GameType, // $c1
+    #[cfg_attr(feature = "serde", serde(with = "tiles_serde"))]
     pub tiles: BitArr!(for 0x100), // $400-$4ff
     pub current_piece: Piece, // $62
     pub next_piece: Piece, // $bf
-    pub score: u32,   // $73-$75
-    pub level: u8,    // $64
-    pub line_count: u16, // $70
+    pub score: u32,     // $73-$75
+    pub level: u8,      // $64
+    pub line_count: u16, // $70-$71, see GameplayState::MAX_LINE_COUNT
     pub play_state_delay: u8, // timer which corresponds to frames where $68 == 7 or $68 == 8
+    pub piece_statistics: [u16; 7], // $3f0-$3fd, indexed by Piece::get_type_index
 }
 
 impl GameplayState<{ Modifier::empty() }> {
@@ -66,6 +74,18 @@ impl GameplayState<{ Modifier::empty() }> {
 }
 
 impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
+    /// The highest line count, after which it wraps around to 0.
+    ///
+    /// The base game holds the line count in two bytes, the first of which
+    /// holds the tens and ones as BCD. The second byte, holding the hundreds,
+    /// is incremented without being decimal adjusted, so it counts up to 255.
+    pub const MAX_LINE_COUNT: u16 = 25599;
+
+    /// The highest value of a piece statistic, after which it wraps around to
+    /// 0. As with the line count, the hundreds byte of the base game's
+    /// statistics is not decimal adjusted.
+    pub const MAX_PIECE_STATISTIC: u16 = 25599;
+
     /// Creates a `GameplayState` with a [`Modifier`].
     ///
     /// Example:
@@ -100,6 +120,7 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
     ) -> Self {
         let mut state = Self {
             dead: false,
+            finished: false,
             previous_input,
             score: 0,
             random: random.clone(),
@@ -127,9 +148,11 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
             frame_counter,
             paused: false,
             play_state_delay: 0,
+            piece_statistics: [0; 7],
         };
 
         state.current_piece = state.random.get_piece();
+        state.increment_piece_statistic();
         state.random.cycle();
         state.next_piece = state.random.get_piece();
 
@@ -155,6 +178,8 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
             2, 2, 2,
         ];
 
+        // pieces drop every frame from level 29 up to level 255, after which
+        // the level wraps around to 0
         if usize::from(self.level) < LEVEL_DELAYS.len() {
             LEVEL_DELAYS[self.level as usize]
         } else {
@@ -163,8 +188,14 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
     }
 
     /// Steps to the next state.
+    ///
+    /// The state remains unchanged once the game is lost or, in B-type games,
+    /// once the goal of clearing 25 lines is reached.
+    ///
+    /// The A+B+Select+Start combo does not pause the game. Leaving gameplay
+    /// through the combo is only modelled by [`State::step`](crate::State::step).
     pub fn step(&mut self, input: Input) {
-        if self.dead {
+        if self.dead || self.finished {
             return;
         }
 
@@ -199,21 +230,26 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
     fn step_main_logic(&mut self, input: Input) {
         if self.game_mode_state == GameModeState::HandleGameplay {
             self.fall_timer += 1;
-            if self.play_state_delay > 0 {
+            if self.play_state_delay == 2 && self.game_type == GameType::B && self.line_count == 0 {
+                // the B-type goal check runs on the first frame of the delay
+                self.finished = true;
+            } else if self.play_state_delay > 0 {
                 self.play_state_delay -= 1;
             } else {
                 self.run_play_state_operation(input);
             }
             self.game_mode_state = GameModeState::HandleStartButton;
 
-            if self.dead || input == Input::Right | Input::Left | Input::Down
+            if self.dead || self.finished || input == Input::Right | Input::Left | Input::Down
             // bug from base game - holding right, left and down causes the frame to end early
             {
                 return;
             }
         }
 
+        // the base game checks for the reset combo before the start button
         if self.game_mode_state == GameModeState::HandleStartButton
+            && input != RESET_COMBO
             && input.difference(self.previous_input).contains(Input::Start)
         {
             self.paused = true;
@@ -314,13 +350,20 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
         match self.game_type {
             GameType::A => {
                 for _ in 0..self.cleared_lines {
-                    self.line_count += 1;
-                    self.line_count %= 10000;
+                    self.line_count = (self.line_count + 1) % (Self::MAX_LINE_COUNT + 1);
                     if self.line_count % 10 == 0 {
-                        let lines_middle_digits = (self.line_count / 10) as u8 % 100;
-                        let target_level = lines_middle_digits + 6 * (lines_middle_digits / 10);
-                        if self.level < target_level {
-                            self.level += 1;
+                        // the base game's target level is made up of the low
+                        // digit of its hundreds byte, which is not decimal
+                        // adjusted, and the tens digit; e.g. $a4 at 1040 lines
+                        let hundreds_byte = (self.line_count / 100) as u8;
+                        let tens = (self.line_count / 10 % 10) as u8;
+                        let target_level = hundreds_byte << 4 | tens;
+
+                        // the base game compares the level with the target
+                        // level as signed numbers, and lets the level wrap
+                        // around from 255 to 0
+                        if (self.level.wrapping_sub(target_level) as i8) < 0 {
+                            self.level = self.level.wrapping_add(1);
                         }
                     }
                 }
@@ -335,8 +378,13 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
         }
 
         if self.hold_down_points >= 2 {
-            // buggy score addition logic from base game
-            let low_digits = from_bcd(to_bcd((self.score % 100) as u8) + self.hold_down_points - 1);
+            // buggy score addition logic from base game, with its 8-bit
+            // arithmetic
+            let low_digits = from_bcd(
+                to_bcd((self.score % 100) as u8)
+                    .wrapping_add(self.hold_down_points)
+                    .wrapping_sub(1),
+            );
             self.score = self.score / 100 * 100;
             self.score += u32::from(low_digits);
             if low_digits >= 100 {
@@ -345,8 +393,10 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
         }
         self.hold_down_points = 0;
 
-        self.score += u32::from(BASE_LINE_CLEAR_POINTS[self.cleared_lines as usize])
-            * u32::from(self.level + 1);
+        // the points are added level + 1 times, which is 256 times at level 255
+        let points = u32::from(BASE_LINE_CLEAR_POINTS[self.cleared_lines as usize])
+            * (u32::from(self.level) + 1);
+        self.score = self.score.saturating_add(points);
 
         if !MODIFIER.uncapped_score {
             self.score = self.score.min(999_999);
@@ -367,10 +417,18 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
         self.play_state = PlayState::MoveTetrimino;
         self.current_piece_x = 5;
         self.current_piece = self.next_piece;
+        self.increment_piece_statistic();
         self.next_piece = self.random.get_piece();
         self.drop_autorepeat = 0;
     }
 
+    fn increment_piece_statistic(&mut self) {
+        if let Some(type_index) = self.current_piece.get_type_index() {
+            let statistic = &mut self.piece_statistics[type_index];
+            *statistic = (*statistic + 1) % (Self::MAX_PIECE_STATISTIC + 1);
+        }
+    }
+
     fn try_shift_piece(&mut self, input: Input) {
         if input.contains(Input::Down) {
             return;
@@ -566,3 +624,32 @@ impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
         self.tiles[..tiles_to_clear].fill(false);
     }
 }
+
+// serializes tiles as 0x20 bytes, each holding 8 tiles starting from the least
+// significant bit, so that the format does not depend on the platform's word
+// size
+#[cfg(feature = "serde")]
+mod tiles_serde {
+    use bitvec::prelude::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        tiles: &BitArr!(for 0x100),
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut bytes = [0u8; 0x20];
+        bytes
+            .view_bits_mut::<Lsb0>()
+            .clone_from_bitslice(&tiles[..0x100]);
+        bytes.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BitArr!(for 0x100), D::Error> {
+        let bytes = <[u8; 0x20]>::deserialize(deserializer)?;
+        let mut tiles = BitArray::ZERO;
+        tiles[..0x100].clone_from_bitslice(bytes.view_bits::<Lsb0>());
+        Ok(tiles)
+    }
+}
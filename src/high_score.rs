@@ -0,0 +1,42 @@
+/// An entry of the high score table, which holds the top 3 scores of each game
+/// type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HighScore {
+    pub name: [u8; 6], // ASCII, padded with spaces
+    pub score: u32,
+    pub level: u8,
+}
+
+impl HighScore {
+    /// The table held by the ROM, before any game is played. Indexed by
+    /// [`GameType`](crate::game_type::GameType).
+    pub const DEFAULT_TABLE: [[HighScore; 3]; 2] = [
+        [
+            HighScore::new(*b"HOWARD", 10000, 9),
+            HighScore::new(*b"OTASAN", 7500, 5),
+            HighScore::new(*b"LANCE ", 5000, 0),
+        ],
+        [
+            HighScore::new(*b"ALEX  ", 2000, 9),
+            HighScore::new(*b"TONY  ", 1000, 5),
+            HighScore::new(*b"NINTEN", 500, 0),
+        ],
+    ];
+
+    /// The name given to new entries, as entering a name is not modelled.
+    pub const BLANK_NAME: [u8; 6] = *b"      ";
+
+    #[must_use]
+    pub const fn new(name: [u8; 6], score: u32, level: u8) -> Self {
+        Self { name, score, level }
+    }
+
+    /// Gets the entry's name, without padding.
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        std::str::from_utf8(&self.name)
+            .unwrap_or_default()
+            .trim_end()
+    }
+}
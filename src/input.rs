@@ -1,7 +1,13 @@
+use crate::movie_error::MovieError;
 use bitflags::bitflags;
 
 bitflags! {
     #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    #[cfg_attr(
+        feature = "serde",
+        derive(serde::Serialize, serde::Deserialize),
+        serde(transparent)
+    )]
     pub struct Input: u8 {
         const Right = 0x01;
         const Left = 0x02;
@@ -15,9 +21,14 @@ bitflags! {
 }
 
 impl Input {
-    pub fn from_fm2_string(string: &String) -> Result<Input, String> {
-        if string.len() != 8 {
-            return Err("cannot create input from fm2 string of length != 8".into());
+    pub fn from_fm2_string(string: &str) -> Result<Input, MovieError> {
+        let width = string.chars().count();
+        if width != 8 {
+            return Err(MovieError::WrongFieldWidth {
+                line: None,
+                expected: 8,
+                found: width,
+            });
         }
 
         let mut input_byte = 0;
@@ -29,6 +40,23 @@ impl Input {
 
         Ok(Input::from_bits_retain(input_byte))
     }
+
+    #[must_use]
+    pub fn to_fm2_string(self) -> String {
+        const BUTTON_CHARACTERS: [char; 8] = ['R', 'L', 'D', 'U', 'T', 'S', 'B', 'A'];
+
+        BUTTON_CHARACTERS
+            .iter()
+            .enumerate()
+            .map(|(index, character)| {
+                if self.bits() & (1 << index) != 0 {
+                    *character
+                } else {
+                    '.'
+                }
+            })
+            .collect()
+    }
 }
 
 impl Default for Input {
@@ -20,8 +20,9 @@
 //! let movie = Movie::from_fm2("inputs.fm2").expect("File not found.");
 //!
 //! let mut state = State::new();
-//! for input in movie.inputs {
-//!     state.step(input);
+//! for (frame_index, input) in movie.inputs.iter().enumerate() {
+//!     state.apply_command(movie.get_command(frame_index));
+//!     state.step(*input);
 //! }
 //!
 //! match state.gameplay_state {
@@ -37,12 +38,21 @@
 //! Although this crate aims for substantive accuracy to the original game,
 //! there ineviteably exist differences between the two. The known deviations
 //! are:
-//! * Console resets and the in-game A+B+select+start button combo to reset the
-//!   game are unsupported.
-//! * The demo never plays.
-//! * Gameplay continues after the B-type goal is reached.
-//! * The state remains unchanged once the player loses.
-//! * Lag and program counter corruption at high levels is not emulated.
+//! * The states after a console reset and after the in-game
+//!   A+B+select+start button combo have not been verified against movies
+//!   which reset mid-run.
+//! * The demo never plays. Its inputs come from the ROM's demo button table,
+//!   a recording several hundred bytes long which, unlike small tables such
+//!   as the level drop delays, has not been transcribed into this crate, and
+//!   no movie idling on the title screen long enough to reach the demo is
+//!   available to check such a transcription against.
+//! * The state remains unchanged once the player loses, or once the B-type
+//!   goal is reached. Every movie in the test suite ends on the frame the game
+//!   ends, so the curtain animation, high score entry and return to the menus
+//!   that follow could not be checked against a single frame of real play.
+//! * Lag and program counter corruption at high levels is not emulated: both
+//!   depend on the CPU cycles spent by the game's routines, which are not
+//!   tracked.
 //! * Some operations have been intentionally altered in ways that simplify
 //!   logic, but do not ultimately affect accuracy. An example of this is the
 //!   `shift_autorepeat` field in `GameplayState` counting down, rather than up
@@ -51,30 +61,53 @@
 //! Further modifications to the game, such as preventing the score from being
 //! capped at 999999, can also be applied: see the [`Modifier`] type for
 //! details.
+//!
+//! With the `serde` feature enabled, states and the types they are made up of
+//! implement `Serialize` and `Deserialize`.
 
 #![allow(incomplete_features)]
 #![feature(adt_const_params)]
 
+mod bk2;
+mod command;
+mod fm2_reader;
 mod game_mode_state;
 mod game_type;
 mod gameplay_state;
+mod high_score;
 mod input;
 mod menu_mode;
+mod mmo;
 mod modifier;
 mod movie;
+mod movie_error;
+mod movie_frame;
+mod movie_metadata;
+mod music_type;
+mod nes_ram;
 mod piece;
 mod play_state;
 mod random;
+mod savestate;
+mod savestate_error;
 mod state;
 
+pub use command::*;
+pub use fm2_reader::*;
 pub use game_mode_state::*;
 pub use game_type::*;
 pub use gameplay_state::*;
+pub use high_score::*;
 pub use input::*;
 pub use menu_mode::*;
 pub use modifier::*;
 pub use movie::*;
+pub use movie_error::*;
+pub use movie_frame::*;
+pub use movie_metadata::*;
+pub use music_type::*;
 pub use piece::*;
 pub use play_state::*;
 pub use random::*;
+pub use savestate_error::*;
 pub use state::*;
@@ -0,0 +1,183 @@
+use crate::{
+    command::Command, input::Input, movie::Movie, movie_error::MovieError, movie_frame::MovieFrame,
+    movie_metadata::MovieMetadata,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path,
+};
+use zip::ZipArchive;
+
+impl Movie {
+    /// Reads a movie from an MMO archive, as used by Mesen.
+    ///
+    /// Movies recorded for a PAL or Dendy console, with a ROM other than NTSC
+    /// NES Tetris, with devices other than standard controllers or starting
+    /// from a savestate are rejected with a [`MovieError`]. As with
+    /// [`Movie::from_bk2`], the resulting `metadata` lists FCEUX's checksum of
+    /// NES Tetris once the ROM's SHA-1 hash has been verified.
+    pub fn from_mmo(path: impl AsRef<Path>) -> Result<Self, MovieError> {
+        Self::from_mmo_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a movie in the MMO format from an arbitrary reader, such as
+    /// a [`Cursor`](std::io::Cursor) over an in-memory archive.
+    ///
+    /// See [`Movie::from_mmo`] for details.
+    pub fn from_mmo_reader(reader: impl Read + Seek) -> Result<Self, MovieError> {
+        let mut archive = ZipArchive::new(reader)?;
+        if archive.by_name("SaveState.mst").is_ok() {
+            return Err(MovieError::UnsupportedFeature {
+                feature: "starting from a savestate".into(),
+            });
+        }
+
+        let mut game_settings = String::new();
+        archive
+            .by_name("GameSettings.txt")?
+            .read_to_string(&mut game_settings)?;
+        let metadata = Self::parse_mmo_game_settings(&game_settings)?;
+
+        let mut input_log = String::new();
+        archive
+            .by_name("Input.txt")?
+            .read_to_string(&mut input_log)?;
+        let frames = Self::parse_mmo_input_log(&input_log)?;
+
+        Ok(Movie::from_frames(metadata, frames))
+    }
+
+    fn parse_mmo_game_settings(game_settings: &str) -> Result<MovieMetadata, MovieError> {
+        let mut metadata = MovieMetadata::new();
+        let mut sha1 = None;
+        for line in game_settings.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "GameFile" => metadata.rom_filename = value.into(),
+                "SHA1" => sha1 = Some(value.to_ascii_uppercase()),
+                "Region" => match value {
+                    "Auto" | "NTSC" => (),
+                    "PAL" => metadata.pal = true,
+                    region => {
+                        return Err(MovieError::UnsupportedRegion {
+                            region: region.into(),
+                        })
+                    }
+                },
+                "Controller1" | "Controller2" | "Controller3" | "Controller4"
+                | "ExpansionDevice" => {
+                    if value != "StandardController" && value != "None" {
+                        // with a Four Score, controllers 3 and 4 share the
+                        // ports of controllers 1 and 2
+                        let port = match key {
+                            "Controller1" | "Controller3" => 0,
+                            "Controller2" | "Controller4" => 1,
+                            _ => 2,
+                        };
+                        return Err(MovieError::UnsupportedDevice {
+                            port,
+                            device: value.into(),
+                        });
+                    }
+                    metadata.other_keys.push((key.into(), value.into()));
+                }
+                _ => metadata.other_keys.push((key.into(), value.into())),
+            }
+        }
+
+        if metadata.pal {
+            return Err(MovieError::PalRegion);
+        }
+        match sha1 {
+            Some(sha1) if sha1 == MovieMetadata::TETRIS_ROM_SHA1 => (),
+            sha1 => {
+                return Err(MovieError::RomMismatch {
+                    checksum: sha1.unwrap_or_default(),
+                })
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    fn parse_mmo_input_log(input_log: &str) -> Result<Vec<MovieFrame>, MovieError> {
+        // buttons of a standard controller, in the order Mesen logs them
+        const BUTTONS: [Input; 8] = [
+            Input::Up,
+            Input::Down,
+            Input::Left,
+            Input::Right,
+            Input::Start,
+            Input::Select,
+            Input::B,
+            Input::A,
+        ];
+
+        fn is_pressed(character: char) -> bool {
+            character != '.' && character != ' '
+        }
+
+        let mut frames = Vec::new();
+        for (line_index, line) in input_log.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line
+                .strip_prefix('|')
+                .ok_or_else(|| MovieError::BadInputLine {
+                    line: line_index + 1,
+                    content: line.into(),
+                })?
+                .split('|')
+                .peekable();
+
+            // recent versions of Mesen log reset and power cycle button presses
+            // in a field preceding the controllers
+            let mut command = Command::empty();
+            if let Some(system_actions) = fields.next_if(|f| f.chars().count() == 2) {
+                let mut system_actions = system_actions.chars();
+                if system_actions.next().map_or(false, is_pressed) {
+                    command |= Command::SoftReset;
+                }
+                if system_actions.next().map_or(false, is_pressed) {
+                    command |= Command::PowerCycle;
+                }
+            }
+
+            // a field per controller follows, which is empty for unused ports;
+            // only controller 1 is required
+            let mut controller_inputs = [Input::empty(); 4];
+            for (index, controller) in fields.take(4).enumerate() {
+                let width = controller.chars().count();
+                if width != BUTTONS.len() && (index == 0 || width != 0) {
+                    return Err(MovieError::WrongFieldWidth {
+                        line: Some(line_index + 1),
+                        expected: BUTTONS.len(),
+                        found: width,
+                    });
+                }
+
+                for (character, button) in controller.chars().zip(BUTTONS) {
+                    if is_pressed(character) {
+                        controller_inputs[index] |= button;
+                    }
+                }
+            }
+
+            let [input, other_inputs @ ..] = controller_inputs;
+            frames.push(MovieFrame {
+                input,
+                other_inputs,
+                command,
+            });
+        }
+
+        Ok(frames)
+    }
+}
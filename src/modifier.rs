@@ -5,6 +5,7 @@
 /// [`GameplayState`](crate::gameplay_state::GameplayState).
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Modifier {
     /// Prevents the score from being capped at 999999.
     pub uncapped_score: bool,
@@ -1,40 +1,202 @@
-use crate::input::Input;
-use regex::Regex;
-use static_init::dynamic;
+use crate::{
+    command::Command, fm2_reader::Fm2Reader, input::Input, movie_error::MovieError,
+    movie_frame::MovieFrame, movie_metadata::MovieMetadata,
+};
 use std::{
-    error::Error,
     fs::File,
-    io::{self, BufRead, BufReader},
+    io::{self, BufRead, BufReader, BufWriter, Write},
     path::Path,
 };
 
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Movie {
-    pub inputs: Vec<Input>,
+    pub metadata: MovieMetadata,
+    pub inputs: Vec<Input>, // controller 1, the only one read by the game
+    pub other_inputs: Vec<[Input; 3]>, // controllers 2 to 4, frames past the end have no input
+    pub commands: Vec<Command>, // frames past the end of this list have no commands
 }
 
 impl Movie {
-    pub fn from_fm2(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
-        #[dynamic]
-        static REGEX_INPUT: Regex = Regex::new(r"^\|\d+\|([^|]{8})\|").unwrap();
-
-        let file = File::open(path)?;
-        let lines = BufReader::new(file)
-            .lines()
-            .collect::<Result<Vec<String>, io::Error>>()?
-            .into_iter()
-            .skip_while(|l| !l.starts_with('|'));
-
-        let mut inputs = Vec::new();
-        for line in lines {
-            match REGEX_INPUT.captures(&line) {
-                Some(captures) => {
-                    let input = Input::from_fm2_string(&captures.get(1).unwrap().as_str().into())?;
-                    inputs.push(input);
+    /// Creates a `Movie` with default [`MovieMetadata`].
+    #[must_use]
+    pub fn new(inputs: Vec<Input>) -> Self {
+        Self {
+            metadata: MovieMetadata::new(),
+            other_inputs: vec![[Input::empty(); 3]; inputs.len()],
+            commands: vec![Command::empty(); inputs.len()],
+            inputs,
+        }
+    }
+
+    /// Creates a `Movie` from a sequence of frames.
+    #[must_use]
+    pub fn from_frames(
+        metadata: MovieMetadata,
+        frames: impl IntoIterator<Item = MovieFrame>,
+    ) -> Self {
+        let mut movie = Self {
+            metadata,
+            inputs: Vec::new(),
+            other_inputs: Vec::new(),
+            commands: Vec::new(),
+        };
+
+        for frame in frames {
+            movie.inputs.push(frame.input);
+            movie.other_inputs.push(frame.other_inputs);
+            movie.commands.push(frame.command);
+        }
+
+        movie
+    }
+
+    /// Gets the command of a frame, given its zero-based index.
+    #[must_use]
+    pub fn get_command(&self, frame_index: usize) -> Command {
+        self.commands.get(frame_index).copied().unwrap_or_default()
+    }
+
+    /// Gets the inputs of controllers 2 to 4 on a frame, given its zero-based
+    /// index.
+    ///
+    /// Without a Four Score, controller 2 is the one in the second port, and
+    /// controllers 3 and 4 have no input.
+    #[must_use]
+    pub fn get_other_inputs(&self, frame_index: usize) -> [Input; 3] {
+        self.other_inputs
+            .get(frame_index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Checks whether any button is pressed on a controller other than
+    /// controller 1.
+    ///
+    /// The game ignores these controllers, so such movies still play back
+    /// correctly, but the inputs may have been meant for controller 1.
+    #[must_use]
+    pub fn uses_other_controllers(&self) -> bool {
+        self.other_inputs
+            .iter()
+            .flatten()
+            .any(|input| !input.is_empty())
+    }
+
+    /// Reads a movie from a file in the FM2 format, as used by FCEUX.
+    ///
+    /// Movies recorded on a PAL console, with a ROM other than NTSC NES
+    /// Tetris, with devices other than standard controllers or relying on
+    /// unsupported features are rejected with a [`MovieError`].
+    pub fn from_fm2(path: impl AsRef<Path>) -> Result<Self, MovieError> {
+        Self::from_fm2_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a movie in the FM2 format from a string.
+    ///
+    /// See [`Movie::from_fm2`] for details.
+    pub fn from_fm2_str(string: &str) -> Result<Self, MovieError> {
+        Self::from_fm2_reader(string.as_bytes())
+    }
+
+    /// Reads a movie in the FM2 format from an arbitrary reader.
+    ///
+    /// See [`Movie::from_fm2`] for details. To play back a movie without
+    /// storing all of its inputs, use [`Fm2Reader`] instead.
+    pub fn from_fm2_reader(reader: impl BufRead) -> Result<Self, MovieError> {
+        let fm2_reader = Fm2Reader::new(reader)?;
+        let metadata = fm2_reader.metadata().clone();
+        let frames = fm2_reader.collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_frames(metadata, frames))
+    }
+
+    /// Writes the movie to a file in the FM2 format, as used by FCEUX.
+    ///
+    /// The resulting file can be read back with [`Movie::from_fm2`].
+    pub fn to_fm2(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = File::create(path)?;
+        self.write_fm2(BufWriter::new(file))
+    }
+
+    /// Writes the movie in the FM2 format to an arbitrary writer.
+    ///
+    /// The header is written from `metadata`. If the metadata has no GUID, one
+    /// is derived from the inputs, so identical movies are always written
+    /// identically.
+    pub fn write_fm2(&self, mut writer: impl Write) -> io::Result<()> {
+        let metadata = &self.metadata;
+        let guid = if metadata.guid.is_empty() {
+            self.get_guid()
+        } else {
+            metadata.guid.clone()
+        };
+
+        writeln!(writer, "version {}", metadata.version)?;
+        writeln!(writer, "emuVersion {}", metadata.emu_version)?;
+        writeln!(writer, "rerecordCount {}", metadata.rerecord_count)?;
+        writeln!(writer, "palFlag {}", u8::from(metadata.pal))?;
+        writeln!(writer, "romFilename {}", metadata.rom_filename)?;
+        if let Some(rom_checksum) = &metadata.rom_checksum {
+            writeln!(writer, "romChecksum {rom_checksum}")?;
+        }
+        writeln!(writer, "guid {guid}")?;
+        writeln!(writer, "fourscore {}", u8::from(metadata.fourscore))?;
+        writeln!(writer, "microphone {}", u8::from(metadata.microphone))?;
+        for (index, port) in metadata.ports.iter().enumerate() {
+            writeln!(writer, "port{index} {port}")?;
+        }
+        writeln!(writer, "FDS {}", u8::from(metadata.fds))?;
+        writeln!(writer, "NewPPU {}", u8::from(metadata.new_ppu))?;
+        writeln!(writer, "RAMInitOption {}", metadata.ram_init_option)?;
+        writeln!(writer, "RAMInitSeed {}", metadata.ram_init_seed)?;
+        for (key, value) in &metadata.other_keys {
+            writeln!(writer, "{key} {value}")?;
+        }
+        for comment in &metadata.comments {
+            writeln!(writer, "comment {comment}")?;
+        }
+
+        let controller_count = if metadata.fourscore { 4 } else { 2 };
+        for (frame_index, input) in self.inputs.iter().enumerate() {
+            let [input_2, input_3, input_4] = self.get_other_inputs(frame_index);
+            let mut fields = [*input, input_2, input_3, input_4].map(Input::to_fm2_string);
+            if !metadata.fourscore {
+                for (field, &device) in fields.iter_mut().zip(&metadata.ports[..2]) {
+                    if device != MovieMetadata::DEVICE_GAMEPAD {
+                        field.clear();
+                    }
                 }
-                None => return Err("non-input line in fm2 input log section".into()),
             }
+
+            // the expansion port's field is always left empty
+            writeln!(
+                writer,
+                "|{}|{}||",
+                self.get_command(frame_index).bits(),
+                fields[..controller_count].join("|")
+            )?;
         }
 
-        Ok(Movie { inputs })
+        writer.flush()
+    }
+
+    fn get_guid(&self) -> String {
+        // two rounds of 64-bit FNV-1a with distinct offset bases make up the 128
+        // bits of the GUID
+        let hash = |offset_basis: u64| {
+            self.inputs.iter().fold(offset_basis, |hash, input| {
+                (hash ^ u64::from(input.bits())).wrapping_mul(0x0100_0000_01b3)
+            })
+        };
+        let high = hash(0xcbf2_9ce4_8422_2325);
+        let low = hash(0x8422_2325_cbf2_9ce4);
+
+        format!(
+            "{:08X}-{:04X}-{:04X}-{:04X}-{:012X}",
+            high >> 32,
+            (high >> 16) & 0xffff,
+            high & 0xffff,
+            low >> 48,
+            low & 0xffff_ffff_ffff,
+        )
     }
 }
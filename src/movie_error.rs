@@ -0,0 +1,119 @@
+use std::{error::Error, fmt::Display, io};
+use zip::result::ZipError;
+
+/// An error encountered while loading a [`Movie`](crate::movie::Movie).
+///
+/// Line numbers start at 1 and refer to the file the error was found in; for
+/// archive-based formats, this is the relevant file within the archive.
+#[derive(Debug)]
+pub enum MovieError {
+    /// The movie could not be read.
+    Io(io::Error),
+
+    /// The movie archive is corrupt or lacks a required file.
+    Archive(ZipError),
+
+    /// A header line could not be parsed.
+    MalformedHeader { line: usize, content: String },
+
+    /// An input log line could not be parsed.
+    BadInputLine { line: usize, content: String },
+
+    /// An input log field has the wrong number of characters. `line` is `None`
+    /// if the field was parsed on its own, outside of a movie.
+    WrongFieldWidth {
+        line: Option<usize>,
+        expected: usize,
+        found: usize,
+    },
+
+    /// The movie relies on a feature which is not emulated, such as starting
+    /// from a savestate.
+    UnsupportedFeature { feature: String },
+
+    /// A port holds a device other than a standard controller. Ports are
+    /// numbered as in FM2 headers: 0 and 1 are the controller ports and 2 is
+    /// the Famicom expansion port.
+    UnsupportedDevice { port: usize, device: String },
+
+    /// The movie was recorded on a PAL console, whose version of the game is
+    /// not modelled.
+    PalRegion,
+
+    /// The movie was recorded on a console of a region other than NTSC or PAL.
+    UnsupportedRegion { region: String },
+
+    /// The movie was recorded with a ROM other than the NTSC release of NES
+    /// Tetris.
+    RomMismatch { checksum: String },
+
+    /// The movie was recorded with an emulator core which does not emulate an
+    /// NES.
+    UnsupportedCore { core: String },
+}
+
+impl Display for MovieError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read movie: {error}"),
+            Self::Archive(error) => write!(f, "could not read movie archive: {error}"),
+            Self::MalformedHeader { line, content } => {
+                write!(f, "malformed header on line {line}: {content}")
+            }
+            Self::BadInputLine { line, content } => {
+                write!(f, "malformed input on line {line}: {content}")
+            }
+            Self::WrongFieldWidth {
+                line,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "input field of width {found} where {expected} was expected"
+                )?;
+                if let Some(line) = line {
+                    write!(f, " on line {line}")?;
+                }
+                Ok(())
+            }
+            Self::UnsupportedFeature { feature } => {
+                write!(f, "unsupported movie feature: {feature}")
+            }
+            Self::UnsupportedDevice { port, device } => {
+                write!(f, "unsupported device in port {port}: {device}")
+            }
+            Self::PalRegion => write!(f, "movie targets a PAL console"),
+            Self::UnsupportedRegion { region } => write!(f, "unsupported console region: {region}"),
+            Self::RomMismatch { checksum } => {
+                write!(f, "movie targets a different rom (checksum {checksum})")
+            }
+            Self::UnsupportedCore { core } => write!(f, "unsupported emulator core: {core}"),
+        }
+    }
+}
+
+impl Error for MovieError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Archive(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MovieError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ZipError> for MovieError {
+    fn from(error: ZipError) -> Self {
+        match error {
+            ZipError::Io(error) => Self::Io(error),
+            error => Self::Archive(error),
+        }
+    }
+}
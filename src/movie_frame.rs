@@ -0,0 +1,9 @@
+use crate::{command::Command, input::Input};
+
+/// The input and console-level events of a single movie frame.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MovieFrame {
+    pub input: Input,
+    pub other_inputs: [Input; 3], // controllers 2 to 4, see `Movie::other_inputs`
+    pub command: Command,
+}
@@ -0,0 +1,71 @@
+/// Header information of a movie.
+///
+/// Field names follow the keys of the FM2 header. Keys which have no
+/// corresponding field are preserved in `other_keys`, in their original order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MovieMetadata {
+    pub version: u32,                 // version
+    pub emu_version: u32,             // emuVersion
+    pub rerecord_count: u32,          // rerecordCount
+    pub pal: bool,                    // palFlag
+    pub rom_filename: String,         // romFilename
+    pub rom_checksum: Option<String>, // romChecksum
+    pub guid: String,                 // guid
+    pub fourscore: bool,              // fourscore
+    pub microphone: bool,             // microphone
+    pub ports: [u8; 3],               // port0, port1, port2
+    pub fds: bool,                    // FDS
+    pub new_ppu: bool,                // NewPPU
+    pub ram_init_option: u32,         // RAMInitOption
+    pub ram_init_seed: u32,           // RAMInitSeed
+    pub comments: Vec<String>,        // comment, which may appear multiple times
+    pub other_keys: Vec<(String, String)>,
+}
+
+impl MovieMetadata {
+    /// The `romChecksum` of the NTSC release of NES Tetris, as calculated by
+    /// FCEUX.
+    pub const TETRIS_ROM_CHECKSUM: &'static str = "base64:Ww5XFVjIx5aTe5avRpVhxg==";
+
+    /// The SHA-1 hash of the NTSC release of NES Tetris, excluding the iNES
+    /// header, as recorded by BizHawk and Mesen.
+    pub const TETRIS_ROM_SHA1: &'static str = "77747840541BFC62A28A5957692A98C550BD6B2B";
+
+    /// The FM2 device type of an empty port.
+    pub const DEVICE_NONE: u8 = 0;
+
+    /// The FM2 device type of a standard controller.
+    pub const DEVICE_GAMEPAD: u8 = 1;
+
+    /// Creates `MovieMetadata` describing an NTSC NES Tetris movie recorded
+    /// with a standard controller in each of the first two ports.
+    ///
+    /// The GUID is left empty.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            version: 3,
+            emu_version: 22020,
+            rerecord_count: 0,
+            pal: false,
+            rom_filename: "Tetris (USA)".into(),
+            rom_checksum: Some(Self::TETRIS_ROM_CHECKSUM.into()),
+            guid: String::new(),
+            fourscore: false,
+            microphone: false,
+            ports: [1, 1, 0],
+            fds: false,
+            new_ppu: false,
+            ram_init_option: 0,
+            ram_init_seed: 0,
+            comments: Vec::new(),
+            other_keys: Vec::new(),
+        }
+    }
+}
+
+impl Default for MovieMetadata {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,8 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MusicType {
+    Music1,
+    Music2,
+    Music3,
+    Off,
+}
@@ -0,0 +1,500 @@
+use crate::{
+    game_mode_state::GameModeState, game_type::GameType, gameplay_state::GameplayState,
+    high_score::HighScore, input::Input, menu_mode::MenuMode, modifier::Modifier,
+    music_type::MusicType, piece::Piece, play_state::PlayState, random::Random,
+    savestate::validate, savestate_error::SavestateError, state::State,
+};
+use bitvec::prelude::*;
+
+// addresses of the base game's variables; those of the player 1 block at
+// $60-$7f are used for gameplay variables
+const RNG_SEED: usize = 0x17;
+const SPAWN_ID: usize = 0x19;
+const SPAWN_COUNT: usize = 0x1a;
+const TETRIMINO_X: usize = 0x60;
+const TETRIMINO_Y: usize = 0x61;
+const CURRENT_PIECE: usize = 0x62;
+const LEVEL_NUMBER: usize = 0x64;
+const FALL_TIMER: usize = 0x65;
+const AUTOREPEAT_X: usize = 0x66;
+const START_LEVEL: usize = 0x67;
+const PLAY_STATE: usize = 0x68;
+const VRAM_ROW: usize = 0x69;
+const AUTOREPEAT_Y: usize = 0x6e;
+const HOLD_DOWN_POINTS: usize = 0x6f;
+const LINES: usize = 0x70;
+const ROW_Y: usize = 0x72;
+const SCORE: usize = 0x73;
+const COMPLETED_LINES: usize = 0x76;
+const LINE_INDEX: usize = 0x77;
+const START_HEIGHT: usize = 0x79;
+const GAME_MODE_STATE: usize = 0xa7;
+const GENERAL_COUNTER: usize = 0xa8;
+const SELECTING_LEVEL_OR_HEIGHT: usize = 0xad;
+const FRAME_COUNTER: usize = 0xb1;
+const RENDER_MODE: usize = 0xbd;
+const NEXT_PIECE: usize = 0xbf;
+const GAME_MODE: usize = 0xc0;
+const GAME_TYPE: usize = 0xc1;
+const MUSIC_TYPE: usize = 0xc2;
+const SLEEP_COUNTER: usize = 0xc3;
+const HELD_BUTTONS: usize = 0xf7;
+const STATISTICS: usize = 0x3f0;
+const PLAYFIELD: usize = 0x400;
+// each game type has 4 entries in the table, the last of which is only used
+// while inserting a new score
+const HIGH_SCORE_NAMES: usize = 0x700;
+const HIGH_SCORE_SCORES: usize = 0x730;
+const HIGH_SCORE_LEVELS: usize = 0x748;
+const HIGH_SCORE_ENTRIES: usize = 4;
+
+const GAME_MODE_PLAY: u8 = 4;
+const EMPTY_TILE: u8 = 0xef;
+const FILLED_TILE: u8 = 0x7b;
+// the characters of high score names, indexed by their values in RAM
+const NAME_CHARACTERS: &[u8; 43] = b" ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789,/()\".";
+
+impl<const MODIFIER: Modifier> State<MODIFIER> {
+    /// Creates a `State` from a dump of the NES' 2 KiB of internal RAM, taken
+    /// between two frames.
+    ///
+    /// On menu screens, the dump must be taken while the game waits for
+    /// input: the game's progress through a screen transition, or through
+    /// the delay preceding the first possible input on the copyright screen,
+    /// is not fully held in RAM. The demo is not supported.
+    ///
+    /// See [`GameplayState::from_nes_ram`] for details on gameplay.
+    pub fn from_nes_ram(ram: &[u8; 0x800]) -> Result<Self, SavestateError> {
+        const MENU_MODES: [MenuMode; 4] = [
+            MenuMode::CopyrightScreen,
+            MenuMode::TitleScreen,
+            MenuMode::GameTypeSelect,
+            MenuMode::LevelSelect,
+        ];
+
+        let game_mode = ram[GAME_MODE];
+        let menu_mode = match MENU_MODES.get(usize::from(game_mode)) {
+            Some(menu_mode) => *menu_mode,
+            None if game_mode == GAME_MODE_PLAY => MenuMode::LevelSelect,
+            None => return Err(SavestateError::UnsupportedGameMode { game_mode }),
+        };
+
+        let gameplay_state = if game_mode == GAME_MODE_PLAY {
+            Some(GameplayState::from_nes_ram(ram)?)
+        } else {
+            None
+        };
+
+        let copyright_skip_timer = if menu_mode == MenuMode::CopyrightScreen {
+            validate("sleep_counter", ram[SLEEP_COUNTER], |v| v == 0)?;
+            ram[GENERAL_COUNTER]
+        } else {
+            0
+        };
+
+        Ok(Self {
+            delay_timer: 0,
+            change_to_gameplay_state: gameplay_state.is_some(),
+            menu_mode,
+            copyright_skip_timer,
+            previous_input: Input::from_bits_retain(ram[HELD_BUTTONS]),
+            random: read_random(ram)?,
+            frame_counter: ram[FRAME_COUNTER] % 4,
+            selecting_height: read_bool(ram, SELECTING_LEVEL_OR_HEIGHT, "selecting_height")?,
+            game_type: read_game_type(ram)?,
+            selected_level: validate("selected_level", ram[START_LEVEL], |v| v < 30)?,
+            selected_height: validate("selected_height", ram[START_HEIGHT], |v| v < 6)?,
+            music_type: read_music_type(ram)?,
+            high_scores: read_high_scores(ram)?,
+            gameplay_state,
+        })
+    }
+
+    /// Creates an image of the NES' 2 KiB of internal RAM, holding the state's
+    /// variables at their addresses in the base game.
+    ///
+    /// Variables which are not modelled are left zeroed, apart from the empty
+    /// playfield. See [`State::write_nes_ram`] to overwrite only the modelled
+    /// variables of an existing RAM image.
+    #[must_use]
+    pub fn to_nes_ram(&self) -> [u8; 0x800] {
+        let mut ram = [0; 0x800];
+        ram[PLAYFIELD..PLAYFIELD + 0x100].fill(EMPTY_TILE);
+        self.write_nes_ram(&mut ram);
+        ram
+    }
+
+    /// Writes the state's variables to their addresses in an image of the
+    /// NES' internal RAM, inverting [`State::from_nes_ram`].
+    ///
+    /// The remaining frames of a screen transition, which the base game does
+    /// not hold in RAM, are not written. Characters of high score names which
+    /// the base game cannot display are written as spaces. See
+    /// [`GameplayState::write_nes_ram`] for details on gameplay.
+    pub fn write_nes_ram(&self, ram: &mut [u8; 0x800]) {
+        if let Some(gameplay_state) = &self.gameplay_state {
+            gameplay_state.write_nes_ram(ram);
+        } else {
+            ram[GAME_MODE] = self.menu_mode as u8;
+            ram[HELD_BUTTONS] = self.previous_input.bits();
+            write_random(ram, &self.random);
+            write_frame_counter(ram, self.frame_counter);
+            ram[GAME_TYPE] = self.game_type as u8;
+        }
+
+        if self.menu_mode == MenuMode::CopyrightScreen {
+            ram[GENERAL_COUNTER] = self.copyright_skip_timer;
+            ram[SLEEP_COUNTER] = 0;
+        }
+        ram[SELECTING_LEVEL_OR_HEIGHT] = self.selecting_height.into();
+        ram[START_LEVEL] = self.selected_level;
+        ram[START_HEIGHT] = self.selected_height;
+        ram[MUSIC_TYPE] = self.music_type as u8;
+        write_high_scores(ram, &self.high_scores);
+    }
+}
+
+impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
+    /// Creates a `GameplayState` from a dump of the NES' 2 KiB of internal
+    /// RAM, taken between two frames of gameplay.
+    ///
+    /// Variables which this crate represents differently from the base game,
+    /// such as the BCD-encoded score or the RNG seed, are converted. A dump
+    /// holding values which the game can never reach, or taken outside of
+    /// gameplay, is rejected with a [`SavestateError`].
+    ///
+    /// What follows a successful B-type goal check is not modelled, so states
+    /// are never read as `finished`. Finished states are written as they were
+    /// right before the goal check.
+    pub fn from_nes_ram(ram: &[u8; 0x800]) -> Result<Self, SavestateError> {
+        let game_mode = ram[GAME_MODE];
+        if game_mode != GAME_MODE_PLAY {
+            return Err(SavestateError::UnsupportedGameMode { game_mode });
+        }
+
+        // the game stays in the start button handling state while paused, but
+        // stops rendering the playfield
+        let paused = ram[RENDER_MODE] == 0;
+        let game_mode_state = match ram[GAME_MODE_STATE] {
+            _ if paused => GameModeState::HandleGameplay,
+            2 | 8 => GameModeState::HandleGameplay,
+            5..=7 => GameModeState::HandleStartButton, // the frame ended early
+            value => {
+                return Err(SavestateError::InvalidField {
+                    field: "game_mode_state",
+                    value: value.into(),
+                })
+            }
+        };
+
+        // the delay before spawning the next piece corresponds to the base
+        // game's B-type goal check and garbage handling play states
+        let (play_state, play_state_delay, dead) = match ram[PLAY_STATE] {
+            1 => (PlayState::MoveTetrimino, 0, false),
+            2 => (PlayState::LockTetrimino, 0, false),
+            3 => (PlayState::CheckForCompletedRows, 0, false),
+            4 => (PlayState::DoNothing, 0, false),
+            5 => (PlayState::UpdateLinesAndStatistics, 0, false),
+            6 => (PlayState::SpawnNextTetrimino, 2, false),
+            7 => (PlayState::SpawnNextTetrimino, 1, false),
+            8 => (PlayState::SpawnNextTetrimino, 0, false),
+            10 => (PlayState::LockTetrimino, 0, true),
+            value => {
+                return Err(SavestateError::InvalidField {
+                    field: "play_state",
+                    value: value.into(),
+                })
+            }
+        };
+
+        let mut tiles = BitArray::ZERO;
+        for (index, tile) in ram[PLAYFIELD..PLAYFIELD + 0x100].iter().enumerate() {
+            tiles.set(index, *tile != EMPTY_TILE);
+        }
+
+        let mut piece_statistics = [0; 7];
+        for (type_index, statistic) in piece_statistics.iter_mut().enumerate() {
+            let address = STATISTICS + type_index * 2;
+            *statistic = read_bcd(&ram[address..address + 1], "piece_statistics")? as u16
+                + u16::from(ram[address + 1]) * 100;
+        }
+
+        let score = read_bcd(&ram[SCORE..SCORE + 3], "score")?;
+        Ok(Self {
+            dead,
+            finished: false,
+            paused,
+            game_mode_state,
+            play_state,
+            checked_row_offset: validate("checked_row_offset", ram[LINE_INDEX], |v| v <= 4)?,
+            update_lines_delay: 5u8.saturating_sub(ram[ROW_Y]), // the base game counts up
+            previous_input: Input::from_bits_retain(ram[HELD_BUTTONS]),
+            random: read_random(ram)?,
+            frame_counter: ram[FRAME_COUNTER] % 4,
+            // the playfield is fully rendered once 20 rows are copied to VRAM,
+            // 4 rows per frame
+            rendering_delay: ram[VRAM_ROW].min(20) / 4,
+            cleared_lines: validate("cleared_lines", ram[COMPLETED_LINES], |v| v <= 4)?,
+            current_piece_x: validate("current_piece_x", ram[TETRIMINO_X] as i8, |v| {
+                (0..10).contains(&v)
+            })?,
+            current_piece_y: validate("current_piece_y", ram[TETRIMINO_Y] as i8, |v| {
+                (0..20).contains(&v)
+            })?,
+            hold_down_points: ram[HOLD_DOWN_POINTS],
+            fall_timer: ram[FALL_TIMER],
+            drop_autorepeat: ram[AUTOREPEAT_Y] as i8,
+            shift_autorepeat: 15u8.saturating_sub(ram[AUTOREPEAT_X]), // the base game counts up
+            game_type: read_game_type(ram)?,
+            tiles,
+            current_piece: read_piece(ram, CURRENT_PIECE, "current_piece")?,
+            next_piece: read_piece(ram, NEXT_PIECE, "next_piece")?,
+            score,
+            level: ram[LEVEL_NUMBER],
+            line_count: read_bcd(&ram[LINES..LINES + 1], "line_count")? as u16
+                + u16::from(ram[LINES + 1]) * 100,
+            play_state_delay,
+            piece_statistics,
+        })
+    }
+
+    /// Creates an image of the NES' 2 KiB of internal RAM, holding the state's
+    /// variables at their addresses in the base game.
+    ///
+    /// Variables which are not modelled are left zeroed, apart from the empty
+    /// playfield. See [`GameplayState::write_nes_ram`] to overwrite only the
+    /// modelled variables of an existing RAM image.
+    #[must_use]
+    pub fn to_nes_ram(&self) -> [u8; 0x800] {
+        let mut ram = [0; 0x800];
+        ram[PLAYFIELD..PLAYFIELD + 0x100].fill(EMPTY_TILE);
+        self.write_nes_ram(&mut ram);
+        ram
+    }
+
+    /// Writes the state's variables to their addresses in an image of the
+    /// NES' internal RAM, inverting [`GameplayState::from_nes_ram`].
+    ///
+    /// As the colors of tiles are not modelled, filled tiles of the playfield
+    /// which are empty in `ram` are written as $7b, while already filled
+    /// tiles are kept. A score above 999999, which the base game cannot hold,
+    /// is written as 999999.
+    pub fn write_nes_ram(&self, ram: &mut [u8; 0x800]) {
+        ram[GAME_MODE] = GAME_MODE_PLAY;
+        ram[GAME_MODE_STATE] = match self.game_mode_state {
+            _ if self.paused => 7,
+            GameModeState::HandleGameplay => 2,
+            GameModeState::HandleStartButton => 5,
+            GameModeState::Unpause => 7,
+        };
+        ram[RENDER_MODE] = if self.paused { 0 } else { 3 };
+        ram[PLAY_STATE] = match self.play_state {
+            _ if self.dead => 10,
+            PlayState::MoveTetrimino => 1,
+            PlayState::LockTetrimino => 2,
+            PlayState::CheckForCompletedRows => 3,
+            PlayState::DoNothing => 4,
+            PlayState::UpdateLinesAndStatistics => 5,
+            PlayState::SpawnNextTetrimino => 8 - self.play_state_delay.min(2),
+        };
+
+        ram[LINE_INDEX] = self.checked_row_offset;
+        ram[ROW_Y] = 5u8.saturating_sub(self.update_lines_delay);
+        ram[HELD_BUTTONS] = self.previous_input.bits();
+        write_random(ram, &self.random);
+        write_frame_counter(ram, self.frame_counter);
+        ram[VRAM_ROW] = if self.rendering_delay >= 5 {
+            0x20
+        } else {
+            self.rendering_delay * 4
+        };
+        ram[COMPLETED_LINES] = self.cleared_lines;
+        ram[TETRIMINO_X] = self.current_piece_x as u8;
+        ram[TETRIMINO_Y] = self.current_piece_y as u8;
+        ram[HOLD_DOWN_POINTS] = self.hold_down_points;
+        ram[FALL_TIMER] = self.fall_timer;
+        ram[AUTOREPEAT_Y] = self.drop_autorepeat as u8;
+        ram[AUTOREPEAT_X] = 15u8.saturating_sub(self.shift_autorepeat);
+        ram[GAME_TYPE] = self.game_type as u8;
+
+        for (index, tile) in ram[PLAYFIELD..PLAYFIELD + 0x100].iter_mut().enumerate() {
+            if !self.tiles[index] {
+                *tile = EMPTY_TILE;
+            } else if *tile == EMPTY_TILE {
+                *tile = FILLED_TILE;
+            }
+        }
+
+        ram[CURRENT_PIECE] = self.current_piece as u8;
+        ram[NEXT_PIECE] = self.next_piece as u8;
+        write_bcd(&mut ram[SCORE..SCORE + 3], self.score.min(999_999));
+        ram[LEVEL_NUMBER] = self.level;
+        write_bcd(&mut ram[LINES..LINES + 1], u32::from(self.line_count % 100));
+        ram[LINES + 1] = (self.line_count / 100) as u8;
+        for (type_index, statistic) in self.piece_statistics.iter().enumerate() {
+            let address = STATISTICS + type_index * 2;
+            write_bcd(&mut ram[address..address + 1], u32::from(*statistic % 100));
+            ram[address + 1] = (*statistic / 100) as u8;
+        }
+    }
+}
+
+fn read_bool(
+    ram: &[u8; 0x800],
+    address: usize,
+    field: &'static str,
+) -> Result<bool, SavestateError> {
+    Ok(validate(field, ram[address], |v| v <= 1)? == 1)
+}
+
+fn read_game_type(ram: &[u8; 0x800]) -> Result<GameType, SavestateError> {
+    match ram[GAME_TYPE] {
+        0 => Ok(GameType::A),
+        1 => Ok(GameType::B),
+        value => Err(SavestateError::InvalidField {
+            field: "game_type",
+            value: value.into(),
+        }),
+    }
+}
+
+fn read_music_type(ram: &[u8; 0x800]) -> Result<MusicType, SavestateError> {
+    match ram[MUSIC_TYPE] {
+        0 => Ok(MusicType::Music1),
+        1 => Ok(MusicType::Music2),
+        2 => Ok(MusicType::Music3),
+        3 => Ok(MusicType::Off),
+        value => Err(SavestateError::InvalidField {
+            field: "music_type",
+            value: value.into(),
+        }),
+    }
+}
+
+fn read_piece(
+    ram: &[u8; 0x800],
+    address: usize,
+    field: &'static str,
+) -> Result<Piece, SavestateError> {
+    match Piece::from_id(ram[address]) {
+        Some(piece) if piece != Piece::None => Ok(piece),
+        _ => Err(SavestateError::InvalidField {
+            field,
+            value: ram[address].into(),
+        }),
+    }
+}
+
+fn read_random(ram: &[u8; 0x800]) -> Result<Random, SavestateError> {
+    let seed = u16::from_be_bytes([ram[RNG_SEED], ram[RNG_SEED + 1]]);
+    let index = Random::get_index_of_seed(seed).ok_or(SavestateError::InvalidField {
+        field: "random.index",
+        value: seed.into(),
+    })?;
+
+    Ok(Random {
+        index,
+        piece_counter: ram[SPAWN_COUNT] % 8,
+        last_piece: read_piece(ram, SPAWN_ID, "random.last_piece")?,
+    })
+}
+
+fn write_random(ram: &mut [u8; 0x800], random: &Random) {
+    ram[RNG_SEED..RNG_SEED + 2].copy_from_slice(&random.get_seed().to_be_bytes());
+    ram[SPAWN_ID] = random.last_piece as u8;
+    ram[SPAWN_COUNT] = random.piece_counter;
+}
+
+// the base game's frame counter is 16 bits wide, while only its lowest 2 bits
+// are modelled
+fn write_frame_counter(ram: &mut [u8; 0x800], frame_counter: u8) {
+    ram[FRAME_COUNTER] = ram[FRAME_COUNTER] & !3 | frame_counter;
+}
+
+// reads a little-endian BCD number
+fn read_bcd(bytes: &[u8], field: &'static str) -> Result<u32, SavestateError> {
+    let mut number = 0;
+    for byte in bytes.iter().rev() {
+        for digit in [byte >> 4, byte & 0xf] {
+            if digit > 9 {
+                return Err(SavestateError::InvalidField {
+                    field,
+                    value: (*byte).into(),
+                });
+            }
+
+            number = number * 10 + u32::from(digit);
+        }
+    }
+
+    Ok(number)
+}
+
+// writes a little-endian BCD number, filling the given bytes
+fn write_bcd(bytes: &mut [u8], mut number: u32) {
+    for byte in bytes {
+        let low_digit = (number % 10) as u8;
+        let high_digit = (number / 10 % 10) as u8;
+        *byte = high_digit << 4 | low_digit;
+        number /= 100;
+    }
+}
+
+// unlike other numbers, high scores are stored as big-endian BCD
+fn read_high_scores(ram: &[u8; 0x800]) -> Result<[[HighScore; 3]; 2], SavestateError> {
+    let mut high_scores = HighScore::DEFAULT_TABLE;
+    for (game_type_index, entries) in high_scores.iter_mut().enumerate() {
+        for (entry_index, high_score) in entries.iter_mut().enumerate() {
+            let index = game_type_index * HIGH_SCORE_ENTRIES + entry_index;
+
+            let mut name = [0; 6];
+            for (letter, value) in name
+                .iter_mut()
+                .zip(&ram[HIGH_SCORE_NAMES + index * 6..][..6])
+            {
+                *letter = *NAME_CHARACTERS.get(usize::from(*value)).ok_or(
+                    SavestateError::InvalidField {
+                        field: "high_scores.name",
+                        value: (*value).into(),
+                    },
+                )?;
+            }
+
+            let mut score_bytes = [0; 3];
+            score_bytes.copy_from_slice(&ram[HIGH_SCORE_SCORES + index * 3..][..3]);
+            score_bytes.reverse();
+
+            *high_score = HighScore::new(
+                name,
+                read_bcd(&score_bytes, "high_scores.score")?,
+                ram[HIGH_SCORE_LEVELS + index],
+            );
+        }
+    }
+
+    Ok(high_scores)
+}
+
+fn write_high_scores(ram: &mut [u8; 0x800], high_scores: &[[HighScore; 3]; 2]) {
+    for (game_type_index, entries) in high_scores.iter().enumerate() {
+        for (entry_index, high_score) in entries.iter().enumerate() {
+            let index = game_type_index * HIGH_SCORE_ENTRIES + entry_index;
+
+            for (value, letter) in ram[HIGH_SCORE_NAMES + index * 6..][..6]
+                .iter_mut()
+                .zip(high_score.name)
+            {
+                *value = NAME_CHARACTERS
+                    .iter()
+                    .position(|character| *character == letter)
+                    .unwrap_or(0) as u8;
+            }
+
+            let score_bytes = &mut ram[HIGH_SCORE_SCORES + index * 3..][..3];
+            write_bcd(score_bytes, high_score.score.min(999_999));
+            score_bytes.reverse();
+
+            ram[HIGH_SCORE_LEVELS + index] = high_score.level;
+        }
+    }
+}
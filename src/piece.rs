@@ -1,4 +1,5 @@
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Piece {
     TUp = 0,
     TRight = 1,
@@ -23,6 +24,47 @@ pub enum Piece {
 }
 
 impl Piece {
+    /// Gets a piece from its ID in the base game (as stored in $62 or $bf),
+    /// which is also its discriminant.
+    #[must_use]
+    pub fn from_id(id: u8) -> Option<Self> {
+        const PIECES: [Piece; 20] = [
+            Piece::TUp,
+            Piece::TRight,
+            Piece::TDown,
+            Piece::TLeft,
+            Piece::JUp,
+            Piece::JRight,
+            Piece::JDown,
+            Piece::JLeft,
+            Piece::ZHorizontal,
+            Piece::ZVertical,
+            Piece::O,
+            Piece::SHorizontal,
+            Piece::SVertical,
+            Piece::LUp,
+            Piece::LRight,
+            Piece::LDown,
+            Piece::LLeft,
+            Piece::IVertical,
+            Piece::IHorizontal,
+            Piece::None,
+        ];
+
+        PIECES.get(usize::from(id)).copied()
+    }
+
+    /// Gets the index of the piece's type in the order of the base game's
+    /// statistics panel (T, J, Z, O, S, L, I), as used by
+    /// [`GameplayState::piece_statistics`](crate::GameplayState::piece_statistics).
+    /// Returns `None` for [`Piece::None`].
+    #[must_use]
+    pub fn get_type_index(self) -> Option<usize> {
+        const TYPE_INDICES: [usize; 19] = [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 3, 4, 4, 5, 5, 5, 5, 6, 6];
+
+        TYPE_INDICES.get(self as usize).copied()
+    }
+
     #[must_use]
     pub fn get_clockwise_rotation(self) -> Self {
         const CLOCKWISE_ROTATIONS: [Piece; 19] = {
@@ -1,6 +1,7 @@
 use crate::piece::Piece;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Random {
     pub index: u16,
     pub piece_counter: u8,
@@ -8,7 +9,25 @@ pub struct Random {
 }
 
 impl Random {
-    const RNG_STATES_COUNT: usize = 32767;
+    /// The number of distinct states of the random number generator.
+    pub const RNG_STATES_COUNT: usize = 32767;
+
+    const RNG_SEEDS: [u16; Random::RNG_STATES_COUNT] = {
+        let mut seeds = [0; Random::RNG_STATES_COUNT];
+
+        let mut current = 0x8988u16;
+        let mut index = 0;
+        while index < Random::RNG_STATES_COUNT {
+            seeds[index] = current;
+
+            let new_bit = ((current >> 9) ^ (current >> 1)) & 1;
+            current = (new_bit << 15) | (current >> 1);
+
+            index += 1;
+        }
+
+        seeds
+    };
 
     #[must_use]
     pub fn new() -> Self {
@@ -41,24 +60,22 @@ impl Random {
 
     #[must_use]
     pub fn get_value(&self) -> u8 {
-        const RNG_VALUES: [u8; Random::RNG_STATES_COUNT] = {
-            let mut values = [0; Random::RNG_STATES_COUNT];
-
-            let mut current = 0x8988u16;
-            let mut index = 0;
-            while index < Random::RNG_STATES_COUNT {
-                values[index] = (current >> 8) as u8;
-
-                let new_bit = ((current >> 9) ^ (current >> 1)) & 1;
-                current = (new_bit << 15) | (current >> 1);
-
-                index += 1;
-            }
+        (self.get_seed() >> 8) as u8
+    }
 
-            values
-        };
+    /// Gets the value of the base game's 16-bit RNG seed ($17-$18, where $17
+    /// is the high byte).
+    #[must_use]
+    pub fn get_seed(&self) -> u16 {
+        Self::RNG_SEEDS[usize::from(self.index)]
+    }
 
-        RNG_VALUES[usize::from(self.index)]
+    /// Gets the `index` corresponding to a value of the base game's RNG seed,
+    /// or `None` if the seed can never be reached.
+    #[must_use]
+    pub fn get_index_of_seed(seed: u16) -> Option<u16> {
+        let index = Self::RNG_SEEDS.iter().position(|s| *s == seed)?;
+        Some(index as u16)
     }
 
     pub fn get_piece(&mut self) -> Piece {
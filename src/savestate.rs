@@ -0,0 +1,400 @@
+use crate::{
+    game_mode_state::GameModeState, game_type::GameType, gameplay_state::GameplayState,
+    high_score::HighScore, input::Input, menu_mode::MenuMode, modifier::Modifier,
+    music_type::MusicType, piece::Piece, play_state::PlayState, random::Random,
+    savestate_error::SavestateError, state::State,
+};
+use bitvec::prelude::*;
+
+// to be incremented whenever the layout of a savestate changes
+const SAVESTATE_VERSION: u8 = 1;
+
+const STATE_TYPE_STATE: u8 = 0;
+const STATE_TYPE_GAMEPLAY_STATE: u8 = 1;
+
+impl<const MODIFIER: Modifier> State<MODIFIER> {
+    /// Encodes the state in a compact binary format.
+    ///
+    /// The data starts with the version of the format and the state's
+    /// [`Modifier`]. It can be decoded with [`State::from_bytes`].
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = SavestateWriter::new(STATE_TYPE_STATE, &MODIFIER);
+        self.write_savestate(&mut writer);
+        writer.bytes
+    }
+
+    /// Decodes a state encoded with [`State::to_bytes`].
+    ///
+    /// Data written with another version of the format or another
+    /// [`Modifier`], or holding values which the game can never reach, is
+    /// rejected with a [`SavestateError`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SavestateError> {
+        let mut reader = SavestateReader::new(bytes, STATE_TYPE_STATE, &MODIFIER)?;
+        let state = Self::read_savestate(&mut reader)?;
+        reader.finish()?;
+        Ok(state)
+    }
+
+    fn write_savestate(&self, writer: &mut SavestateWriter) {
+        writer.write_u16(self.delay_timer);
+        writer.write_bool(self.change_to_gameplay_state);
+        writer.write_u8(self.menu_mode as u8);
+        writer.write_u8(self.copyright_skip_timer);
+        writer.write_u8(self.previous_input.bits());
+        writer.write_random(&self.random);
+        writer.write_u8(self.frame_counter);
+        writer.write_bool(self.selecting_height);
+        writer.write_u8(self.game_type as u8);
+        writer.write_u8(self.selected_level);
+        writer.write_u8(self.selected_height);
+        writer.write_u8(self.music_type as u8);
+        for high_score in self.high_scores.iter().flatten() {
+            writer.bytes.extend_from_slice(&high_score.name);
+            writer.write_u32(high_score.score);
+            writer.write_u8(high_score.level);
+        }
+
+        writer.write_bool(self.gameplay_state.is_some());
+        if let Some(gameplay_state) = &self.gameplay_state {
+            gameplay_state.write_savestate(writer);
+        }
+    }
+
+    fn read_savestate(reader: &mut SavestateReader) -> Result<Self, SavestateError> {
+        const MENU_MODES: [MenuMode; 4] = [
+            MenuMode::CopyrightScreen,
+            MenuMode::TitleScreen,
+            MenuMode::GameTypeSelect,
+            MenuMode::LevelSelect,
+        ];
+        const MUSIC_TYPES: [MusicType; 4] = [
+            MusicType::Music1,
+            MusicType::Music2,
+            MusicType::Music3,
+            MusicType::Off,
+        ];
+
+        let mut state = Self {
+            delay_timer: reader.read_u16()?,
+            change_to_gameplay_state: reader.read_bool("change_to_gameplay_state")?,
+            menu_mode: reader.read_enum("menu_mode", &MENU_MODES)?,
+            copyright_skip_timer: reader.read_u8()?,
+            previous_input: Input::from_bits_retain(reader.read_u8()?),
+            random: reader.read_random()?,
+            frame_counter: validate("frame_counter", reader.read_u8()?, |v| v < 4)?,
+            selecting_height: reader.read_bool("selecting_height")?,
+            game_type: reader.read_enum("game_type", &[GameType::A, GameType::B])?,
+            selected_level: validate("selected_level", reader.read_u8()?, |v| v < 30)?,
+            selected_height: validate("selected_height", reader.read_u8()?, |v| v < 6)?,
+            music_type: reader.read_enum("music_type", &MUSIC_TYPES)?,
+            high_scores: HighScore::DEFAULT_TABLE,
+            gameplay_state: None,
+        };
+
+        for high_score in state.high_scores.iter_mut().flatten() {
+            let name = reader.read_array()?;
+            if let Some(letter) = name.iter().find(|l| !(b' '..=b'~').contains(*l)) {
+                return Err(SavestateError::InvalidField {
+                    field: "high_scores.name",
+                    value: (*letter).into(),
+                });
+            }
+
+            *high_score = HighScore::new(name, reader.read_u32()?, reader.read_u8()?);
+        }
+
+        if reader.read_bool("gameplay_state")? {
+            state.gameplay_state = Some(GameplayState::read_savestate(reader)?);
+        }
+
+        Ok(state)
+    }
+}
+
+impl<const MODIFIER: Modifier> GameplayState<MODIFIER> {
+    /// Encodes the state in a compact binary format.
+    ///
+    /// The data starts with the version of the format and the state's
+    /// [`Modifier`]. It can be decoded with [`GameplayState::from_bytes`].
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = SavestateWriter::new(STATE_TYPE_GAMEPLAY_STATE, &MODIFIER);
+        self.write_savestate(&mut writer);
+        writer.bytes
+    }
+
+    /// Decodes a state encoded with [`GameplayState::to_bytes`].
+    ///
+    /// Data written with another version of the format or another
+    /// [`Modifier`], or holding values which the game can never reach, is
+    /// rejected with a [`SavestateError`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SavestateError> {
+        let mut reader = SavestateReader::new(bytes, STATE_TYPE_GAMEPLAY_STATE, &MODIFIER)?;
+        let state = Self::read_savestate(&mut reader)?;
+        reader.finish()?;
+        Ok(state)
+    }
+
+    fn write_savestate(&self, writer: &mut SavestateWriter) {
+        writer.write_bool(self.dead);
+        writer.write_bool(self.finished);
+        writer.write_bool(self.paused);
+        writer.write_u8(self.game_mode_state as u8);
+        writer.write_u8(self.play_state as u8);
+        writer.write_u8(self.checked_row_offset);
+        writer.write_u8(self.update_lines_delay);
+        writer.write_u8(self.previous_input.bits());
+        writer.write_random(&self.random);
+        writer.write_u8(self.frame_counter);
+        writer.write_u8(self.rendering_delay);
+        writer.write_u8(self.cleared_lines);
+        writer.write_i8(self.current_piece_x);
+        writer.write_i8(self.current_piece_y);
+        writer.write_u8(self.hold_down_points);
+        writer.write_u8(self.fall_timer);
+        writer.write_i8(self.drop_autorepeat);
+        writer.write_u8(self.shift_autorepeat);
+        writer.write_u8(self.game_type as u8);
+        writer.write_u8(self.current_piece as u8);
+        writer.write_u8(self.next_piece as u8);
+        writer.write_u32(self.score);
+        writer.write_u8(self.level);
+        writer.write_u16(self.line_count);
+        writer.write_u8(self.play_state_delay);
+        for statistic in self.piece_statistics {
+            writer.write_u16(statistic);
+        }
+
+        // each byte holds 8 tiles, starting from the least significant bit
+        let mut tiles = [0u8; 0x20];
+        tiles
+            .view_bits_mut::<Lsb0>()
+            .clone_from_bitslice(&self.tiles[..0x100]);
+        writer.bytes.extend_from_slice(&tiles);
+    }
+
+    fn read_savestate(reader: &mut SavestateReader) -> Result<Self, SavestateError> {
+        const GAME_MODE_STATES: [GameModeState; 3] = [
+            GameModeState::HandleGameplay,
+            GameModeState::HandleStartButton,
+            GameModeState::Unpause,
+        ];
+        const PLAY_STATES: [PlayState; 6] = [
+            PlayState::MoveTetrimino,
+            PlayState::LockTetrimino,
+            PlayState::CheckForCompletedRows,
+            PlayState::DoNothing,
+            PlayState::UpdateLinesAndStatistics,
+            PlayState::SpawnNextTetrimino,
+        ];
+
+        let mut state = Self {
+            dead: reader.read_bool("dead")?,
+            finished: reader.read_bool("finished")?,
+            paused: reader.read_bool("paused")?,
+            game_mode_state: reader.read_enum("game_mode_state", &GAME_MODE_STATES)?,
+            play_state: reader.read_enum("play_state", &PLAY_STATES)?,
+            checked_row_offset: validate("checked_row_offset", reader.read_u8()?, |v| v <= 4)?,
+            update_lines_delay: validate("update_lines_delay", reader.read_u8()?, |v| v <= 5)?,
+            previous_input: Input::from_bits_retain(reader.read_u8()?),
+            random: reader.read_random()?,
+            frame_counter: validate("frame_counter", reader.read_u8()?, |v| v < 4)?,
+            rendering_delay: validate("rendering_delay", reader.read_u8()?, |v| v <= 5)?,
+            cleared_lines: validate("cleared_lines", reader.read_u8()?, |v| v <= 4)?,
+            current_piece_x: validate("current_piece_x", reader.read_i8()?, |v| {
+                (0..10).contains(&v)
+            })?,
+            current_piece_y: validate("current_piece_y", reader.read_i8()?, |v| {
+                (0..20).contains(&v)
+            })?,
+            hold_down_points: reader.read_u8()?,
+            fall_timer: reader.read_u8()?,
+            drop_autorepeat: reader.read_i8()?,
+            shift_autorepeat: validate("shift_autorepeat", reader.read_u8()?, |v| v <= 15)?,
+            game_type: reader.read_enum("game_type", &[GameType::A, GameType::B])?,
+            tiles: BitArray::ZERO,
+            current_piece: reader.read_piece("current_piece")?,
+            next_piece: reader.read_piece("next_piece")?,
+            score: validate("score", reader.read_u32()?, |v| {
+                MODIFIER.uncapped_score || v <= 999_999
+            })?,
+            level: reader.read_u8()?,
+            line_count: validate("line_count", reader.read_u16()?, |v| {
+                v <= Self::MAX_LINE_COUNT
+            })?,
+            play_state_delay: validate("play_state_delay", reader.read_u8()?, |v| v <= 2)?,
+            piece_statistics: [0; 7],
+        };
+
+        for statistic in &mut state.piece_statistics {
+            *statistic = validate("piece_statistics", reader.read_u16()?, |v| {
+                v <= Self::MAX_PIECE_STATISTIC
+            })?;
+        }
+
+        let tiles = reader.read_array::<0x20>()?;
+        state.tiles[..0x100].clone_from_bitslice(tiles.view_bits::<Lsb0>());
+
+        Ok(state)
+    }
+}
+
+fn get_modifier_bits(modifier: &Modifier) -> u8 {
+    u8::from(modifier.uncapped_score) | u8::from(modifier.select_adds_20_levels) << 1
+}
+
+pub(crate) fn validate<T: Copy + Into<i64>>(
+    field: &'static str,
+    value: T,
+    is_valid: impl FnOnce(T) -> bool,
+) -> Result<T, SavestateError> {
+    if is_valid(value) {
+        Ok(value)
+    } else {
+        Err(SavestateError::InvalidField {
+            field,
+            value: value.into(),
+        })
+    }
+}
+
+struct SavestateWriter {
+    bytes: Vec<u8>,
+}
+
+impl SavestateWriter {
+    fn new(state_type: u8, modifier: &Modifier) -> Self {
+        Self {
+            bytes: vec![SAVESTATE_VERSION, state_type, get_modifier_bits(modifier)],
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn write_i8(&mut self, value: i8) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_bool(&mut self, value: bool) {
+        self.bytes.push(value.into());
+    }
+
+    fn write_random(&mut self, random: &Random) {
+        self.write_u16(random.index);
+        self.write_u8(random.piece_counter);
+        self.write_u8(random.last_piece as u8);
+    }
+}
+
+struct SavestateReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SavestateReader<'a> {
+    fn new(bytes: &'a [u8], state_type: u8, modifier: &Modifier) -> Result<Self, SavestateError> {
+        let mut reader = Self { bytes };
+
+        let version = reader.read_u8()?;
+        if version != SAVESTATE_VERSION {
+            return Err(SavestateError::UnsupportedVersion { version });
+        }
+
+        if reader.read_u8()? != state_type {
+            return Err(SavestateError::WrongStateType);
+        }
+
+        let modifier_bits = validate("modifier", reader.read_u8()?, |v| v < 4)?;
+        if modifier_bits != get_modifier_bits(modifier) {
+            return Err(SavestateError::ModifierMismatch {
+                expected: modifier.clone(),
+                found: Modifier {
+                    uncapped_score: modifier_bits & 1 != 0,
+                    select_adds_20_levels: modifier_bits & 2 != 0,
+                },
+            });
+        }
+
+        Ok(reader)
+    }
+
+    fn finish(self) -> Result<(), SavestateError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(SavestateError::TrailingBytes {
+                count: self.bytes.len(),
+            })
+        }
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], SavestateError> {
+        if self.bytes.len() < N {
+            return Err(SavestateError::UnexpectedEnd);
+        }
+
+        let (array, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+        Ok(array.try_into().unwrap())
+    }
+
+    fn read_u8(&mut self) -> Result<u8, SavestateError> {
+        Ok(u8::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_i8(&mut self) -> Result<i8, SavestateError> {
+        Ok(i8::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_u16(&mut self) -> Result<u16, SavestateError> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, SavestateError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_bool(&mut self, field: &'static str) -> Result<bool, SavestateError> {
+        Ok(validate(field, self.read_u8()?, |v| v <= 1)? == 1)
+    }
+
+    fn read_enum<T: Copy>(
+        &mut self,
+        field: &'static str,
+        variants: &[T],
+    ) -> Result<T, SavestateError> {
+        let value = validate(field, self.read_u8()?, |v| usize::from(v) < variants.len())?;
+        Ok(variants[usize::from(value)])
+    }
+
+    fn read_piece(&mut self, field: &'static str) -> Result<Piece, SavestateError> {
+        let value = self.read_u8()?;
+        match Piece::from_id(value) {
+            Some(piece) if piece != Piece::None => Ok(piece),
+            _ => Err(SavestateError::InvalidField {
+                field,
+                value: value.into(),
+            }),
+        }
+    }
+
+    fn read_random(&mut self) -> Result<Random, SavestateError> {
+        Ok(Random {
+            index: validate("random.index", self.read_u16()?, |v| {
+                usize::from(v) < Random::RNG_STATES_COUNT
+            })?,
+            piece_counter: validate("random.piece_counter", self.read_u8()?, |v| v < 8)?,
+            last_piece: self.read_piece("random.last_piece")?,
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use crate::modifier::Modifier;
+use std::{error::Error, fmt::Display};
+
+/// An error encountered while decoding a state from bytes produced by
+/// [`State::to_bytes`](crate::state::State::to_bytes) or
+/// [`GameplayState::to_bytes`](crate::gameplay_state::GameplayState::to_bytes),
+/// or from a dump of the NES' RAM.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SavestateError {
+    /// The data ends before the state does.
+    UnexpectedEnd,
+
+    /// The data continues after the end of the state.
+    TrailingBytes { count: usize },
+
+    /// The data was written by an incompatible version of this crate.
+    UnsupportedVersion { version: u8 },
+
+    /// The data holds a `GameplayState` where a `State` was expected, or vice
+    /// versa.
+    WrongStateType,
+
+    /// The data was written for a state with a different [`Modifier`].
+    ModifierMismatch { expected: Modifier, found: Modifier },
+
+    /// The RAM dump was taken in a game mode which cannot be represented, such
+    /// as the demo.
+    UnsupportedGameMode { game_mode: u8 },
+
+    /// A field holds a value which the game can never reach.
+    InvalidField { field: &'static str, value: i64 },
+}
+
+impl Display for SavestateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "savestate ends unexpectedly"),
+            Self::TrailingBytes { count } => {
+                write!(f, "savestate is followed by {count} unexpected bytes")
+            }
+            Self::UnsupportedVersion { version } => {
+                write!(f, "unsupported savestate version: {version}")
+            }
+            Self::WrongStateType => write!(f, "savestate holds a different type of state"),
+            Self::ModifierMismatch { expected, found } => write!(
+                f,
+                "savestate modifier {found:?} does not match expected modifier {expected:?}"
+            ),
+            Self::UnsupportedGameMode { game_mode } => {
+                write!(f, "unsupported game mode: {game_mode}")
+            }
+            Self::InvalidField { field, value } => {
+                write!(f, "invalid value of savestate field {field}: {value}")
+            }
+        }
+    }
+}
+
+impl Error for SavestateError {}
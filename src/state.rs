@@ -1,4 +1,8 @@
-use crate::{input::Input, modifier::Modifier, GameType, GameplayState, MenuMode, Random};
+use crate::{
+    command::Command, game_mode_state::GameModeState, gameplay_state::RESET_COMBO,
+    high_score::HighScore, input::Input, modifier::Modifier, music_type::MusicType, GameType,
+    GameplayState, MenuMode, Random,
+};
 
 /// A general state of the game.
 ///
@@ -8,6 +12,7 @@ use crate::{input::Input, modifier::Modifier, GameType, GameplayState, MenuMode,
 /// The `MODIFIER` const generic specifies game modifiers - see [`Modifier`] for
 /// supported modifiers.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct State<const MODIFIER: Modifier> {
     pub delay_timer: u16,
     pub change_to_gameplay_state: bool,
@@ -20,6 +25,8 @@ pub struct State<const MODIFIER: Modifier> {
     pub game_type: GameType,
     pub selected_level: u8,
     pub selected_height: u8,
+    pub music_type: MusicType,
+    pub high_scores: [[HighScore; 3]; 2], // indexed by GameType, see State::get_high_scores
     pub gameplay_state: Option<GameplayState<MODIFIER>>,
 }
 
@@ -63,6 +70,8 @@ impl<const MODIFIER: Modifier> State<MODIFIER> {
             selected_level: 0,
             selecting_height: false,
             selected_height: 0,
+            music_type: MusicType::Music1,
+            high_scores: HighScore::DEFAULT_TABLE,
             copyright_skip_timer: 0xff,
             delay_timer: 267,
             change_to_gameplay_state: false,
@@ -70,10 +79,88 @@ impl<const MODIFIER: Modifier> State<MODIFIER> {
         }
     }
 
+    /// Gets the high score table of a game type, from first to third place.
+    #[must_use]
+    pub fn get_high_scores(&self, game_type: GameType) -> &[HighScore; 3] {
+        &self.high_scores[game_type as usize]
+    }
+
+    /// Presses the console's reset button.
+    ///
+    /// The game's reset routine reinitializes every modelled variable except
+    /// for the high score table, so the resulting state is the same as after
+    /// [`State::power_cycle`] apart from `high_scores`. As in FCEUX, the reset
+    /// takes effect at the start of the next frame.
+    ///
+    /// The timing of the reset, in particular the RNG and frame counter values
+    /// after it, has not been verified against a movie which resets mid-run.
+    pub fn reset(&mut self) {
+        *self = Self {
+            high_scores: self.high_scores,
+            ..Self::new_with_modifier()
+        };
+    }
+
+    /// Turns the console off and on again.
+    pub fn power_cycle(&mut self) {
+        *self = Self::new_with_modifier();
+    }
+
+    /// Applies the console-level events of a movie frame; to be called before
+    /// [`State::step`] for that frame.
+    ///
+    /// Commands which have no effect on an NES, such as disk system commands,
+    /// are ignored.
+    pub fn apply_command(&mut self, command: Command) {
+        if command.contains(Command::PowerCycle) {
+            self.power_cycle();
+        } else if command.contains(Command::SoftReset) {
+            self.reset();
+        }
+    }
+
     /// Steps to the next state.
+    ///
+    /// Holding A, B, Select and Start during gameplay returns to the
+    /// copyright screen, as long as the game is neither paused nor over and
+    /// the frame does not end early. Unlike the console's reset button, the
+    /// combo keeps the RNG and frame counter running. The copyright screen
+    /// then runs as it does after powering on, less the two frames in which
+    /// the reset routine waits for the PPU to warm up; this timing has not
+    /// been verified against a movie.
     pub fn step(&mut self, input: Input) {
         if let Some(ref mut gameplay_state) = self.gameplay_state {
+            let was_over = gameplay_state.dead || gameplay_state.finished;
+            let was_paused = gameplay_state.paused;
             gameplay_state.step(input);
+            if (gameplay_state.dead || gameplay_state.finished) && !was_over {
+                let game_type = gameplay_state.game_type;
+                let high_score = HighScore::new(
+                    HighScore::BLANK_NAME,
+                    gameplay_state.score,
+                    gameplay_state.level,
+                );
+                self.insert_high_score(game_type, high_score);
+            } else if input == RESET_COMBO
+                && !was_paused
+                && !gameplay_state.dead
+                && !gameplay_state.finished
+                // the frame ended early unless the game mode state is reset
+                // to handling gameplay
+                && gameplay_state.game_mode_state == GameModeState::HandleGameplay
+            {
+                // the copyright screen's initial frames and sleep, during
+                // which input is ignored
+                self.delay_timer = 265;
+                self.random = gameplay_state.random.clone();
+                self.random.cycle_multiple(262);
+                self.frame_counter = (gameplay_state.frame_counter + 2) % 4;
+                self.previous_input = input;
+                self.menu_mode = MenuMode::CopyrightScreen;
+                self.copyright_skip_timer = 0xff;
+                self.change_to_gameplay_state = false;
+                self.gameplay_state = None;
+            }
             return;
         }
 
@@ -104,6 +191,15 @@ impl<const MODIFIER: Modifier> State<MODIFIER> {
         self.previous_input = input;
     }
 
+    // an entry only replaces those with strictly lower scores
+    fn insert_high_score(&mut self, game_type: GameType, high_score: HighScore) {
+        let table = &mut self.high_scores[game_type as usize];
+        if let Some(place) = table.iter().position(|h| high_score.score > h.score) {
+            table[place..].rotate_right(1);
+            table[place] = high_score;
+        }
+    }
+
     fn step_main_logic(&mut self, input: Input) {
         match self.menu_mode {
             MenuMode::CopyrightScreen => self.step_legal_screen(input),
@@ -144,6 +240,20 @@ impl<const MODIFIER: Modifier> State<MODIFIER> {
             Input::Right => {
                 self.game_type = GameType::B;
             }
+            Input::Down => {
+                self.music_type = match self.music_type {
+                    MusicType::Music1 => MusicType::Music2,
+                    MusicType::Music2 => MusicType::Music3,
+                    MusicType::Music3 | MusicType::Off => MusicType::Off,
+                };
+            }
+            Input::Up => {
+                self.music_type = match self.music_type {
+                    MusicType::Music1 | MusicType::Music2 => MusicType::Music1,
+                    MusicType::Music3 => MusicType::Music2,
+                    MusicType::Off => MusicType::Music3,
+                };
+            }
             Input::Start => {
                 self.menu_mode = MenuMode::LevelSelect;
                 self.delay_timer = 4;
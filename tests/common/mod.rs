@@ -0,0 +1,32 @@
+// shared by several test crates, each of which uses only some of these items
+#![allow(dead_code)]
+
+use meta_nestris::{Modifier, Movie, State};
+
+pub type EmptyModifierState = State<{ Modifier::empty() }>;
+
+pub fn load_movie(path: &str) -> Movie {
+    Movie::from_fm2(path)
+        .unwrap_or_else(|error| panic!("could not open movie file: {path}: {error}"))
+}
+
+// plays back a movie from power-on, applying its commands, and calls
+// `on_frame` with the index of each frame and the state following it
+pub fn play_movie(
+    movie: &Movie,
+    mut on_frame: impl FnMut(usize, &EmptyModifierState),
+) -> EmptyModifierState {
+    let mut state = EmptyModifierState::new();
+    for (frame_index, input) in movie.inputs.iter().enumerate() {
+        state.apply_command(movie.get_command(frame_index));
+        state.step(*input);
+        on_frame(frame_index, &state);
+    }
+
+    state
+}
+
+// plays back the movie at `path` from power-on, returning the final state
+pub fn play_movie_file(path: &str) -> EmptyModifierState {
+    play_movie(&load_movie(path), |_, _| ())
+}
@@ -0,0 +1,113 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+mod common;
+
+use common::{load_movie, play_movie_file, EmptyModifierState};
+use meta_nestris::{Command, GameType, HighScore, Input, MenuMode, Random};
+
+#[test]
+fn reset_during_gameplay() {
+    let menuing_movie = load_movie("tests/movies/menuing/level_select_random_input.fm2");
+
+    let mut state = play_movie_file("tests/movies/menuing/gameplay.fm2");
+    assert!(state.gameplay_state.is_some());
+
+    // after a reset, the model plays out exactly as after powering on; this
+    // is unverified against a movie which resets mid-run
+    let mut power_on_state = EmptyModifierState::new();
+    for (frame_index, input) in menuing_movie.inputs.into_iter().enumerate() {
+        if frame_index == 0 {
+            state.apply_command(Command::SoftReset);
+        }
+        state.step(input);
+        power_on_state.step(input);
+
+        assert_eq!(state, power_on_state);
+    }
+}
+
+#[test]
+fn power_cycle() {
+    let mut state = play_movie_file("tests/movies/menuing/gameplay.fm2");
+    state.apply_command(Command::PowerCycle);
+
+    assert_eq!(state, EmptyModifierState::new());
+}
+
+#[test]
+fn high_scores_survive_reset() {
+    let mut state = play_movie_file("tests/movies/death/normal_play.fm2");
+    let gameplay_state = state.gameplay_state.as_ref().unwrap();
+    assert!(gameplay_state.dead);
+
+    // the score of 5200 is placed third, behind the defaults of 10000 and 7500
+    let expected_high_scores = [
+        HighScore::DEFAULT_TABLE[0][0],
+        HighScore::DEFAULT_TABLE[0][1],
+        HighScore::new(HighScore::BLANK_NAME, 5200, gameplay_state.level),
+    ];
+    assert_eq!(gameplay_state.score, 5200);
+    assert_eq!(*state.get_high_scores(GameType::A), expected_high_scores);
+    assert_eq!(
+        state.get_high_scores(GameType::B),
+        &HighScore::DEFAULT_TABLE[1]
+    );
+
+    state.apply_command(Command::SoftReset);
+    assert_eq!(*state.get_high_scores(GameType::A), expected_high_scores);
+
+    state.apply_command(Command::PowerCycle);
+    assert_eq!(state.high_scores, HighScore::DEFAULT_TABLE);
+}
+
+#[test]
+fn reset_combo_during_gameplay() {
+    let reset_combo = Input::A | Input::B | Input::Select | Input::Start;
+
+    let mut state = play_movie_file("tests/movies/menuing/gameplay.fm2");
+    let mut gameplay_state = state.gameplay_state.clone().unwrap();
+
+    // unlike a console reset, the combo keeps the RNG and frame counter running
+    state.step(reset_combo);
+    gameplay_state.step(reset_combo);
+    assert!(state.gameplay_state.is_none());
+    assert_eq!(state.menu_mode, MenuMode::CopyrightScreen);
+    assert!(!gameplay_state.paused);
+
+    // pressing Start right after the combo is ignored during the copyright
+    // screen's sleep
+    state.step(Input::empty());
+    state.step(Input::Start);
+    assert_eq!(state.menu_mode, MenuMode::CopyrightScreen);
+
+    // the menus then play out as after powering on, two frames earlier and
+    // with the RNG and frame counter offset by the frames played before the
+    // combo; this is unverified against a movie using the combo
+    let mut power_on_state = EmptyModifierState::new();
+    for _ in 0..3 {
+        power_on_state.step(Input::empty());
+    }
+    power_on_state.step(Input::Start);
+    // Random::new starts at index 0
+    assert_eq!(
+        state.random.index,
+        (power_on_state.random.index + gameplay_state.random.index)
+            % Random::RNG_STATES_COUNT as u16
+    );
+    let frame_counter_offset = (state.frame_counter + 4 - power_on_state.frame_counter) % 4;
+
+    let menuing_movie = load_movie("tests/movies/menuing/level_select_random_input.fm2");
+    for input in menuing_movie.inputs {
+        state.step(input);
+        power_on_state.step(input);
+
+        // the RNG is not compared, as the height menu cycles it a number of
+        // times depending on its value
+        let mut offset_state = power_on_state.clone();
+        offset_state.random = state.random.clone();
+        offset_state.frame_counter = (offset_state.frame_counter + frame_counter_offset) % 4;
+        assert_eq!(state, offset_state);
+    }
+    assert_eq!(state.menu_mode, MenuMode::LevelSelect);
+}
@@ -0,0 +1,85 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use meta_nestris::{GameType, GameplayState, Input, Modifier, PlayState, Random};
+
+type EmptyModifierGameplayState = GameplayState<{ Modifier::empty() }>;
+
+// steps through the update of the line count, level and score after lines are
+// cleared
+fn clear_lines(
+    game_type: GameType,
+    level: u8,
+    line_count: u16,
+    cleared_lines: u8,
+) -> EmptyModifierGameplayState {
+    let mut state =
+        EmptyModifierGameplayState::new(&Random::new(), 0, Input::empty(), game_type, level, 0);
+    state.line_count = line_count;
+    state.cleared_lines = cleared_lines;
+    state.play_state = PlayState::UpdateLinesAndStatistics;
+    state.step(Input::empty());
+    assert_eq!(state.play_state, PlayState::SpawnNextTetrimino);
+    state
+}
+
+#[test]
+fn line_clears_never_overflow() {
+    let line_counts = [0, 9, 25, 129, 999, 2559, 9999, 25590, 25596, 25599];
+    for level in 0..=255 {
+        for line_count in line_counts {
+            for cleared_lines in 0..=4 {
+                for game_type in [GameType::A, GameType::B] {
+                    let mut state = clear_lines(game_type, level, line_count, cleared_lines);
+                    assert!(state.line_count <= EmptyModifierGameplayState::MAX_LINE_COUNT);
+                    assert!(state.score <= 999_999);
+
+                    // keep playing at the new level, with the next piece dropping
+                    for _ in 0..60 {
+                        state.step(Input::Down);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn soft_drop_points_never_overflow() {
+    for hold_down_points in 0..=255 {
+        let mut state =
+            EmptyModifierGameplayState::new(&Random::new(), 0, Input::empty(), GameType::A, 18, 0);
+        state.score = 999_999;
+        state.hold_down_points = hold_down_points;
+        state.play_state = PlayState::UpdateLinesAndStatistics;
+        state.step(Input::empty());
+        assert!(state.score <= 999_999);
+    }
+}
+
+#[test]
+fn line_count_hundreds_are_not_decimal_adjusted() {
+    // the target level of 1440 lines is 0xe4, rather than 0x44
+    assert_eq!(clear_lines(GameType::A, 144, 1439, 1).level, 145);
+    assert_eq!(clear_lines(GameType::A, 100, 999, 1).level, 101);
+    assert_eq!(clear_lines(GameType::A, 200, 999, 1).level, 200);
+
+    // the target level of 10000 lines is 0x40
+    assert_eq!(clear_lines(GameType::A, 29, 9999, 1).level, 30);
+
+    let state = clear_lines(GameType::A, 29, 25598, 4);
+    assert_eq!(state.line_count, 2);
+}
+
+#[test]
+fn level_uses_signed_comparison() {
+    // 0x82 - 0x02 is negative as a signed byte
+    assert_eq!(clear_lines(GameType::A, 130, 19, 1).level, 131);
+    assert_eq!(clear_lines(GameType::A, 100, 19, 1).level, 100);
+
+    // the target level of 2560 lines is 0x96
+    assert_eq!(clear_lines(GameType::A, 140, 2559, 1).level, 141);
+    assert_eq!(clear_lines(GameType::A, 150, 2559, 1).level, 150);
+
+    assert_eq!(clear_lines(GameType::A, 255, 129, 1).level, 0);
+}
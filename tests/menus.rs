@@ -0,0 +1,43 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+mod common;
+
+use common::EmptyModifierState;
+use meta_nestris::{Input, MenuMode, MusicType};
+
+fn press(state: &mut EmptyModifierState, input: Input) {
+    state.step(input);
+    state.step(Input::empty());
+}
+
+fn go_to_menu(state: &mut EmptyModifierState, menu_mode: MenuMode) {
+    while state.menu_mode != menu_mode || state.delay_timer > 0 {
+        press(state, Input::Start);
+    }
+}
+
+#[test]
+fn music_type_selection() {
+    let mut state = EmptyModifierState::new();
+    go_to_menu(&mut state, MenuMode::GameTypeSelect);
+    assert_eq!(state.music_type, MusicType::Music1);
+
+    for _ in 0..4 {
+        press(&mut state, Input::Down);
+    }
+    assert_eq!(state.music_type, MusicType::Off);
+    press(&mut state, Input::Up);
+    assert_eq!(state.music_type, MusicType::Music3);
+    press(&mut state, Input::Down | Input::Up);
+    assert_eq!(state.music_type, MusicType::Music3);
+
+    // the selection is kept when going back to the title screen
+    press(&mut state, Input::B);
+    assert_eq!(state.menu_mode, MenuMode::TitleScreen);
+    go_to_menu(&mut state, MenuMode::GameTypeSelect);
+    assert_eq!(state.music_type, MusicType::Music3);
+
+    state.reset();
+    assert_eq!(state.music_type, MusicType::Music1);
+}
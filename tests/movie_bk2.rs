@@ -0,0 +1,17 @@
+use meta_nestris::Movie;
+
+#[test]
+fn bk2_matches_fm2_source() {
+    let fm2_movie = Movie::from_fm2("tests/movies/b_type_clear/tasvideos_920m.fm2").unwrap();
+    let bk2_movie =
+        Movie::from_bk2("tests/movies/b_type_clear/tasvideos_920m_converted.bk2").unwrap();
+
+    assert_eq!(bk2_movie.inputs, fm2_movie.inputs);
+    assert_eq!(bk2_movie.other_inputs, fm2_movie.other_inputs);
+    assert_eq!(bk2_movie.commands, fm2_movie.commands);
+    assert_eq!(bk2_movie.metadata.rerecord_count, 2043);
+    assert_eq!(
+        bk2_movie.metadata.rom_checksum,
+        fm2_movie.metadata.rom_checksum
+    );
+}
@@ -0,0 +1,174 @@
+use meta_nestris::{Command, Fm2Reader, Input, Movie, MovieError};
+use std::{fs, io::BufReader};
+
+fn to_fm2_string(movie: &Movie) -> String {
+    let mut fm2 = Vec::new();
+    movie.write_fm2(&mut fm2).unwrap();
+    String::from_utf8(fm2).unwrap()
+}
+
+#[test]
+fn fm2_round_trip() {
+    let movie = Movie::from_fm2("tests/movies/death/normal_play.fm2").unwrap();
+
+    let written_movie = Movie::from_fm2_str(&to_fm2_string(&movie)).unwrap();
+
+    assert_eq!(movie, written_movie);
+}
+
+#[test]
+fn fm2_from_str_and_reader() {
+    let movie = Movie::from_fm2("tests/movies/death/normal_play.fm2").unwrap();
+
+    let fm2 = include_str!("movies/death/normal_play.fm2");
+    assert_eq!(Movie::from_fm2_str(fm2).unwrap(), movie);
+
+    let file = fs::File::open("tests/movies/death/normal_play.fm2").unwrap();
+    let reader = Fm2Reader::new(BufReader::new(file)).unwrap();
+    assert_eq!(*reader.metadata(), movie.metadata);
+    let frames = reader.map(Result::unwrap).collect::<Vec<_>>();
+    assert!(frames.iter().map(|f| f.input).eq(movie.inputs));
+    assert!(frames.iter().map(|f| f.command).eq(movie.commands));
+}
+
+#[test]
+fn fm2_input_log_format() {
+    let movie = Movie::new(vec![
+        Input::empty(),
+        Input::Right | Input::Start,
+        Input::all(),
+    ]);
+
+    let mut fm2 = Vec::new();
+    movie.write_fm2(&mut fm2).unwrap();
+    let fm2 = String::from_utf8(fm2).unwrap();
+
+    let input_log = fm2.lines().skip_while(|l| !l.starts_with('|'));
+    assert!(input_log.eq([
+        "|0|........|........||",
+        "|0|R...T...|........||",
+        "|0|RLDUTSBA|........||",
+    ]));
+    assert!(fm2.contains("romChecksum base64:Ww5XFVjIx5aTe5avRpVhxg==\n"));
+}
+
+#[test]
+fn fm2_header() {
+    let movie = Movie::from_fm2("tests/movies/a_type_999999_score/tasvideos_1596m.fm2").unwrap();
+    let metadata = movie.metadata;
+
+    assert_eq!(metadata.version, 3);
+    assert_eq!(metadata.emu_version, 21020);
+    assert_eq!(metadata.rerecord_count, 1269);
+    assert!(!metadata.pal);
+    assert_eq!(metadata.rom_filename, "Tetris (U) [!]");
+    assert_eq!(metadata.guid, "0C712FCA-C60C-9ED5-4051-CDCFBE413495");
+    assert_eq!(metadata.ports, [1, 0, 0]);
+}
+
+#[test]
+fn fm2_commands() {
+    let movie = Movie::from_fm2("tests/movies/a_type_999999_score/tasvideos_239m.fm2").unwrap();
+    assert_eq!(movie.commands[0], Command::SoftReset);
+    assert!(movie.commands[1..].iter().all(Command::is_empty));
+
+    let movie = Movie::from_fm2("tests/movies/b_type_clear/tasvideos_920m.fm2").unwrap();
+    assert_eq!(movie.commands[0], Command::PowerCycle);
+}
+
+#[test]
+fn fm2_other_controllers() {
+    let movie = Movie::from_fm2("tests/movies/a_type_999999_score/tasvideos_924m.fm2").unwrap();
+    assert!(movie.uses_other_controllers());
+    assert_eq!(movie.get_other_inputs(295)[0], Input::Right);
+
+    let movie = Movie::from_fm2("tests/movies/death/normal_play.fm2").unwrap();
+    assert!(!movie.uses_other_controllers());
+
+    let mut four_score_movie = Movie::new(vec![Input::Start, Input::empty()]);
+    four_score_movie.metadata.fourscore = true;
+    four_score_movie.other_inputs[1] = [Input::A, Input::B, Input::Up];
+    let mut fm2 = Vec::new();
+    four_score_movie.write_fm2(&mut fm2).unwrap();
+    let fm2 = String::from_utf8(fm2).unwrap();
+
+    let input_log = fm2.lines().skip_while(|l| !l.starts_with('|'));
+    assert!(input_log.eq([
+        "|0|....T...|........|........|........||",
+        "|0|........|.......A|......B.|...U....||",
+    ]));
+    let read_movie = Movie::from_fm2_str(&fm2).unwrap();
+    assert_eq!(read_movie.inputs, four_score_movie.inputs);
+    assert_eq!(read_movie.other_inputs, four_score_movie.other_inputs);
+}
+
+#[test]
+fn fm2_incompatible_movies() {
+    let movie = Movie::from_fm2("tests/movies/death/normal_play.fm2").unwrap();
+
+    let mut pal_movie = movie.clone();
+    pal_movie.metadata.pal = true;
+    let error = Movie::from_fm2_str(&to_fm2_string(&pal_movie)).unwrap_err();
+    assert!(matches!(error, MovieError::PalRegion));
+
+    let mut zapper_movie = movie.clone();
+    zapper_movie.metadata.ports[1] = 2;
+    let error = Movie::from_fm2_str(&to_fm2_string(&zapper_movie)).unwrap_err();
+    assert!(matches!(
+        error,
+        MovieError::UnsupportedDevice { port: 1, .. }
+    ));
+
+    let mut no_checksum_movie = movie.clone();
+    no_checksum_movie.metadata.rom_checksum = None;
+    let error = Movie::from_fm2_str(&to_fm2_string(&no_checksum_movie)).unwrap_err();
+    assert!(matches!(
+        error,
+        MovieError::RomMismatch { checksum } if checksum.is_empty()
+    ));
+
+    let mut other_rom_movie = movie;
+    other_rom_movie.metadata.rom_checksum = Some("base64:AAAAAAAAAAAAAAAAAAAAAA==".into());
+    let error = Movie::from_fm2_str(&to_fm2_string(&other_rom_movie)).unwrap_err();
+    assert!(matches!(
+        error,
+        MovieError::RomMismatch { checksum } if checksum == "base64:AAAAAAAAAAAAAAAAAAAAAA=="
+    ));
+}
+
+#[test]
+fn fm2_errors() {
+    let movie = Movie::new(vec![Input::empty(); 3]);
+    let fm2 = to_fm2_string(&movie);
+    let header_length = fm2.lines().take_while(|l| !l.starts_with('|')).count();
+
+    let load_with_replaced_line = |line_index: usize, new_line: &str| {
+        let mut lines = fm2.lines().collect::<Vec<_>>();
+        lines[line_index] = new_line;
+        Movie::from_fm2_str(&lines.join("\n")).unwrap_err()
+    };
+
+    let error = load_with_replaced_line(header_length + 1, "|0|........");
+    assert!(matches!(
+        error,
+        MovieError::BadInputLine { line, content } if line == header_length + 2 && content == "|0|........"
+    ));
+
+    let error = load_with_replaced_line(header_length + 2, "|0|.......|........||");
+    assert!(matches!(
+        error,
+        MovieError::WrongFieldWidth { line: Some(line), expected: 8, found: 7 } if line == header_length + 3
+    ));
+
+    let error = load_with_replaced_line(0, "version three");
+    assert!(matches!(
+        error,
+        MovieError::MalformedHeader { line: 1, content } if content == "version three"
+    ));
+
+    let error = load_with_replaced_line(0, "savestate AAAA");
+    assert!(matches!(error, MovieError::UnsupportedFeature { .. }));
+
+    let error = Movie::from_fm2("tests/movies/nonexistent.fm2").unwrap_err();
+    assert!(matches!(error, MovieError::Io(_)));
+}
@@ -0,0 +1,46 @@
+use meta_nestris::{Movie, MovieError};
+use std::{
+    fs::File,
+    io::{Cursor, Read, Write},
+};
+use zip::{write::FileOptions, ZipArchive, ZipWriter};
+
+const MMO_PATH: &str = "tests/movies/a_type_999999_score/tasvideos_239m_converted.mmo";
+
+#[test]
+fn mmo_matches_fm2_source() {
+    let fm2_movie = Movie::from_fm2("tests/movies/a_type_999999_score/tasvideos_239m.fm2").unwrap();
+    let mmo_movie = Movie::from_mmo(MMO_PATH).unwrap();
+
+    assert_eq!(mmo_movie.inputs, fm2_movie.inputs);
+    assert_eq!(mmo_movie.other_inputs, fm2_movie.other_inputs);
+    assert_eq!(mmo_movie.commands, fm2_movie.commands);
+    assert!(!mmo_movie.metadata.pal);
+}
+
+#[test]
+fn mmo_unsupported_device() {
+    let mut archive = ZipArchive::new(File::open(MMO_PATH).unwrap()).unwrap();
+    let mut zapper_archive = ZipWriter::new(Cursor::new(Vec::new()));
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).unwrap();
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+        if file.name() == "GameSettings.txt" {
+            content = content.replace("Controller2 StandardController", "Controller2 Zapper");
+        }
+
+        zapper_archive
+            .start_file(file.name(), FileOptions::default())
+            .unwrap();
+        zapper_archive.write_all(content.as_bytes()).unwrap();
+    }
+    let mut zapper_archive = zapper_archive.finish().unwrap();
+    zapper_archive.set_position(0);
+
+    let error = Movie::from_mmo_reader(zapper_archive).unwrap_err();
+    assert!(matches!(
+        error,
+        MovieError::UnsupportedDevice { port: 1, .. }
+    ));
+}
@@ -2,10 +2,9 @@
 #![feature(adt_const_params)]
 
 use meta_nestris::Modifier;
-use meta_nestris::{Input, MenuMode, Movie, State};
+use meta_nestris::{Command, Input, MenuMode, Movie, MusicType, Piece, PlayState, State};
 use serde::Deserialize;
-use serde::Deserializer;
-use std::{collections::HashMap, fs::File, path::PathBuf};
+use std::{collections::HashMap, ffi::OsStr, fmt::Debug, fs::File, path::PathBuf};
 
 #[derive(Deserialize)]
 struct MovieData {
@@ -20,28 +19,22 @@ struct MovieCheck {
     score: Option<u32>,
     line_count: Option<u16>,
     dead: Option<bool>,
-    #[serde(default, deserialize_with = "deserialize_menu_mode")]
+    finished: Option<bool>,
     menu_mode: Option<MenuMode>,
     is_gameplay_state: Option<bool>,
-}
-
-pub fn deserialize_menu_mode<'de, D>(deserializer: D) -> Result<Option<MenuMode>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    #[derive(Deserialize)]
-    #[serde(remote = "MenuMode")]
-    enum MenuModeDuplicate {
-        CopyrightScreen,
-        TitleScreen,
-        GameTypeSelect,
-        LevelSelect,
-    }
-
-    #[derive(Deserialize)]
-    struct MenuModeWrapper(#[serde(with = "MenuModeDuplicate")] MenuMode);
-
-    Ok(Option::deserialize(deserializer)?.map(|MenuModeWrapper(menu_mode)| menu_mode))
+    level: Option<u8>,
+    current_piece: Option<Piece>,
+    next_piece: Option<Piece>,
+    piece_x: Option<i8>,
+    piece_y: Option<i8>,
+    rng_index: Option<u16>,
+    frame_counter: Option<u8>,
+    play_state: Option<PlayState>,
+    selected_level: Option<u8>,
+    selected_height: Option<u8>,
+    music_type: Option<MusicType>,
+    piece_statistics: Option<[u16; 7]>, // indexed by Piece::get_type_index
+    playfield: Option<Vec<String>>,     // 20 rows of 10 tiles, 'X' if filled and '.' if empty
 }
 
 #[test]
@@ -50,30 +43,36 @@ fn movie_playback() {
         serde_yaml::from_reader(File::open("tests/movies/metadata.yaml").unwrap()).unwrap();
 
     for movie_data in metadata_json {
-        let movie_full_filepath = PathBuf::from("tests/movies/").join(movie_data.filename);
-        let movie = Movie::from_fm2(&movie_full_filepath).unwrap_or_else(|_| {
+        let movie_full_filepath = PathBuf::from("tests/movies/").join(&movie_data.filename);
+        let movie = match movie_full_filepath.extension().and_then(OsStr::to_str) {
+            Some("bk2") => Movie::from_bk2(&movie_full_filepath),
+            Some("mmo") => Movie::from_mmo(&movie_full_filepath),
+            _ => Movie::from_fm2(&movie_full_filepath),
+        }
+        .unwrap_or_else(|error| {
             panic!(
-                "could not open movie file: {}",
+                "could not open movie file: {}: {error}",
                 movie_full_filepath.display()
             )
         });
-        let inputs = movie.inputs.into_iter();
+        let inputs = movie.inputs.into_iter().zip(movie.commands);
 
         if movie_data.uncapped_score {
             const MODIFIER: Modifier = Modifier {
                 uncapped_score: true,
                 ..Modifier::empty()
             };
-            check_movie::<MODIFIER>(&movie_data.checks, inputs);
+            check_movie::<MODIFIER>(&movie_data.filename, &movie_data.checks, inputs);
         } else {
-            check_movie::<{ Modifier::empty() }>(&movie_data.checks, inputs);
+            check_movie::<{ Modifier::empty() }>(&movie_data.filename, &movie_data.checks, inputs);
         }
     }
 }
 
 fn check_movie<const MODIFIER: Modifier>(
+    filename: &str,
     checks: &HashMap<u32, MovieCheck>,
-    mut inputs: impl Iterator<Item = Input>,
+    mut inputs: impl Iterator<Item = (Input, Command)>,
 ) {
     // may need to play movie beyond final stored input
     // at the same time, do not need to play movie beyond last checked frame
@@ -81,48 +80,150 @@ fn check_movie<const MODIFIER: Modifier>(
 
     let mut state = State::<MODIFIER>::new_with_modifier();
     for frame in 1..=playback_duration {
-        state.step(inputs.next().unwrap_or_default()); // use empty Inputs after final movie input
+        let (input, command) = inputs.next().unwrap_or_default(); // use empty Inputs after final movie input
+        state.apply_command(command);
+        state.step(input);
 
         if let Some(check) = checks.get(&frame) {
-            check_state(&state, check);
+            let mismatches = check_state(&state, check);
+            if !mismatches.is_empty() {
+                panic!(
+                    "{filename}: checks failed at frame {frame}:\n{}",
+                    mismatches.join("\n")
+                );
+            }
         }
     }
 }
 
-fn check_state<const MODIFIER: Modifier>(state: &State<MODIFIER>, check: &MovieCheck) {
-    if let Some(score) = check.score {
-        match &state.gameplay_state {
-            Some(state) => assert_eq!(score, state.score),
-            None => panic!("not in gameplay during score check"),
+// returns a description of every check which fails
+fn check_state<const MODIFIER: Modifier>(
+    state: &State<MODIFIER>,
+    check: &MovieCheck,
+) -> Vec<String> {
+    let mut mismatches = Vec::new();
+
+    match &state.gameplay_state {
+        Some(gameplay_state) => {
+            let m = &mut mismatches;
+            check_value(m, "score", check.score, gameplay_state.score);
+            check_value(m, "line_count", check.line_count, gameplay_state.line_count);
+            check_value(m, "dead", check.dead, gameplay_state.dead);
+            check_value(m, "finished", check.finished, gameplay_state.finished);
+            check_value(m, "level", check.level, gameplay_state.level);
+            check_value(
+                m,
+                "current_piece",
+                check.current_piece,
+                gameplay_state.current_piece,
+            );
+            check_value(m, "next_piece", check.next_piece, gameplay_state.next_piece);
+            check_value(m, "piece_x", check.piece_x, gameplay_state.current_piece_x);
+            check_value(m, "piece_y", check.piece_y, gameplay_state.current_piece_y);
+            check_value(m, "rng_index", check.rng_index, gameplay_state.random.index);
+            check_value(
+                m,
+                "frame_counter",
+                check.frame_counter,
+                gameplay_state.frame_counter,
+            );
+            check_value(m, "play_state", check.play_state, gameplay_state.play_state);
+            check_value(
+                m,
+                "piece_statistics",
+                check.piece_statistics,
+                gameplay_state.piece_statistics,
+            );
+
+            if let Some(playfield) = &check.playfield {
+                if playfield.len() != 20 {
+                    m.push(format!(
+                        "playfield: expected 20 rows, found {}",
+                        playfield.len()
+                    ));
+                }
+
+                for (y, expected_row) in playfield.iter().enumerate().take(20) {
+                    let row = (0..10)
+                        .map(|x| {
+                            if gameplay_state.get_tile(x, y) {
+                                'X'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect::<String>();
+                    let name = format!("playfield row {y}");
+                    check_value(m, &name, Some(expected_row.as_str()), row.as_str());
+                }
+            }
         }
-    }
-
-    if let Some(line_count) = check.line_count {
-        match &state.gameplay_state {
-            Some(state) => assert_eq!(line_count, state.line_count),
-            None => panic!("not in gameplay during line count check"),
+        None => {
+            let gameplay_checks = [
+                ("score", check.score.is_some()),
+                ("line_count", check.line_count.is_some()),
+                ("dead", check.dead.is_some()),
+                ("finished", check.finished.is_some()),
+                ("level", check.level.is_some()),
+                ("current_piece", check.current_piece.is_some()),
+                ("next_piece", check.next_piece.is_some()),
+                ("piece_x", check.piece_x.is_some()),
+                ("piece_y", check.piece_y.is_some()),
+                ("play_state", check.play_state.is_some()),
+                ("piece_statistics", check.piece_statistics.is_some()),
+                ("playfield", check.playfield.is_some()),
+            ];
+            for (name, checked) in gameplay_checks {
+                if checked {
+                    mismatches.push(format!("{name}: not in gameplay"));
+                }
+            }
+
+            let m = &mut mismatches;
+            check_value(m, "menu_mode", check.menu_mode, state.menu_mode);
+            check_value(m, "rng_index", check.rng_index, state.random.index);
+            check_value(m, "frame_counter", check.frame_counter, state.frame_counter);
         }
     }
 
-    if let Some(dead) = check.dead {
-        match &state.gameplay_state {
-            Some(state) => assert_eq!(dead, state.dead),
-            None => panic!("not in gameplay during death check"),
-        }
+    if check.menu_mode.is_some() && state.gameplay_state.is_some() {
+        mismatches.push("menu_mode: in gameplay".into());
     }
 
-    if let Some(menu_mode) = check.menu_mode {
-        match state.gameplay_state {
-            Some(_) => panic!("in gameplay during menu mode check"),
-            None => assert_eq!(menu_mode, state.menu_mode),
-        }
-    }
+    let m = &mut mismatches;
+    check_value(
+        m,
+        "selected_level",
+        check.selected_level,
+        state.selected_level,
+    );
+    check_value(
+        m,
+        "selected_height",
+        check.selected_height,
+        state.selected_height,
+    );
+    check_value(m, "music_type", check.music_type, state.music_type);
+    let is_gameplay_state = state.gameplay_state.is_some();
+    check_value(
+        m,
+        "is_gameplay_state",
+        check.is_gameplay_state,
+        is_gameplay_state,
+    );
+
+    mismatches
+}
 
-    if let Some(is_gameplay_state) = check.is_gameplay_state {
-        if is_gameplay_state && state.gameplay_state.is_none() {
-            panic!("non-gameplay when expecting gameplay");
-        } else if !is_gameplay_state && state.gameplay_state.is_some() {
-            panic!("gameplay when expecting non-gameplay")
+fn check_value<T: PartialEq + Debug>(
+    mismatches: &mut Vec<String>,
+    name: &str,
+    expected: Option<T>,
+    actual: T,
+) {
+    if let Some(expected) = expected {
+        if expected != actual {
+            mismatches.push(format!("{name}: expected {expected:?}, found {actual:?}"));
         }
     }
 }
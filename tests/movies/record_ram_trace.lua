@@ -0,0 +1,36 @@
+-- Records a RAM trace for tests/ram_trace.rs while FCEUX plays back a movie.
+--
+-- Load an FM2 movie for playback, then run this script: the movie is restarted
+-- from its first frame, and after every frame, the bytes of the NES' internal
+-- RAM which tests/ram_trace.rs compares are appended to a file named after the
+-- movie, with the extension .ramtrace. Recording stops once playback ends.
+
+-- the address ranges listed as TRACED_RANGES in tests/ram_trace.rs, in order
+local traced_ranges = {
+    {0x17, 4},
+    {0x60, 0x1a},
+    {0xa7, 2},
+    {0xad, 1},
+    {0xb1, 1},
+    {0xbd, 1},
+    {0xbf, 5},
+    {0xf7, 1},
+    {0x3f0, 0x110},
+}
+
+-- frames played before recording starts would be missing from the trace
+movie.playbeginning()
+assert(movie.framecount() == 0, "playback did not restart from the first frame")
+
+local trace_path = movie.name():gsub("%.fm2$", "") .. ".ramtrace"
+local trace = assert(io.open(trace_path, "wb"))
+
+while movie.mode() == "playback" do
+    emu.frameadvance()
+    for _, range in ipairs(traced_ranges) do
+        trace:write(memory.readbyterange(range[1], range[2]))
+    end
+end
+
+trace:close()
+emu.message("RAM trace written to " .. trace_path)
@@ -0,0 +1,206 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+mod common;
+
+use common::{load_movie, play_movie, EmptyModifierState};
+use meta_nestris::{
+    GameModeState, GameType, GameplayState, HighScore, MenuMode, Modifier, MusicType, Piece,
+    PlayState, SavestateError,
+};
+
+type EmptyModifierGameplayState = GameplayState<{ Modifier::empty() }>;
+
+fn get_gameplay_ram() -> [u8; 0x800] {
+    let mut ram = [0; 0x800];
+    ram[0x17..0x1b].copy_from_slice(&[0x89, 0x88, 0x0e, 0x0b]); // RNG
+    ram[0x60..0x63].copy_from_slice(&[4, 7, 0x12]); // piece x, y and ID
+    ram[0x64] = 18; // level
+    ram[0x66] = 3; // autorepeat x
+    ram[0x68] = 7; // play state
+    ram[0x69] = 0x20; // vram row
+    ram[0x6e] = 0xa0; // autorepeat y
+    ram[0x70..0x72].copy_from_slice(&[0x45, 0x01]); // lines
+    ram[0x73..0x76].copy_from_slice(&[0x56, 0x34, 0x12]); // score
+    ram[0xa7] = 2; // game mode state
+    ram[0xb1] = 0x27; // frame counter
+    ram[0xbd] = 3; // render mode
+    ram[0xbf] = 0x0a; // next piece
+    ram[0xc0] = 4; // game mode
+    ram[0xc1] = 1; // game type
+    ram[0x400..0x500].fill(0xef);
+    ram[0x400 + 195] = 0x7b;
+    ram
+}
+
+#[test]
+fn gameplay_state_from_nes_ram() {
+    let gameplay_state = EmptyModifierGameplayState::from_nes_ram(&get_gameplay_ram()).unwrap();
+
+    assert_eq!(gameplay_state.random.index, 0);
+    assert_eq!(gameplay_state.random.piece_counter, 3);
+    assert_eq!(gameplay_state.random.last_piece, Piece::LRight);
+    assert_eq!(gameplay_state.current_piece_x, 4);
+    assert_eq!(gameplay_state.current_piece_y, 7);
+    assert_eq!(gameplay_state.current_piece, Piece::IHorizontal);
+    assert_eq!(gameplay_state.next_piece, Piece::O);
+    assert_eq!(gameplay_state.level, 18);
+    assert_eq!(gameplay_state.shift_autorepeat, 12);
+    assert_eq!(gameplay_state.drop_autorepeat, -96);
+    assert_eq!(gameplay_state.play_state, PlayState::SpawnNextTetrimino);
+    assert_eq!(gameplay_state.play_state_delay, 1);
+    assert_eq!(gameplay_state.rendering_delay, 5);
+    assert_eq!(gameplay_state.line_count, 145);
+    assert_eq!(gameplay_state.score, 123_456);
+    assert_eq!(
+        gameplay_state.game_mode_state,
+        GameModeState::HandleGameplay
+    );
+    assert_eq!(gameplay_state.frame_counter, 3);
+    assert_eq!(gameplay_state.game_type, GameType::B);
+    assert!(!gameplay_state.paused);
+    assert!(!gameplay_state.dead);
+    assert!(gameplay_state.get_tile(5, 19));
+    assert_eq!(gameplay_state.tiles.count_ones(), 1);
+
+    // the hundreds bytes of the line count and piece statistics are not
+    // decimal adjusted
+    let mut ram = get_gameplay_ram();
+    ram[0x71] = 0x0e;
+    ram[0x3f0..0x3f2].copy_from_slice(&[0x07, 0x0a]);
+    let gameplay_state = EmptyModifierGameplayState::from_nes_ram(&ram).unwrap();
+    assert_eq!(gameplay_state.line_count, 1445);
+    assert_eq!(gameplay_state.piece_statistics[0], 1007);
+    let written_ram = gameplay_state.to_nes_ram();
+    assert_eq!(written_ram[0x70..0x72], [0x45, 0x0e]);
+    assert_eq!(written_ram[0x3f0..0x3f2], [0x07, 0x0a]);
+}
+
+#[test]
+fn state_from_nes_ram() {
+    let mut ram = get_gameplay_ram();
+    ram[0x67] = 9; // start level
+    ram[0xad] = 1; // selecting height
+    ram[0xc0] = 3; // game mode
+    ram[0xc2] = 2; // music type
+
+    let state = EmptyModifierState::from_nes_ram(&ram).unwrap();
+    assert_eq!(state.menu_mode, MenuMode::LevelSelect);
+    assert_eq!(state.selected_level, 9);
+    assert!(state.selecting_height);
+    assert_eq!(state.music_type, MusicType::Music3);
+    assert!(state.gameplay_state.is_none());
+
+    ram[0xc0] = 4;
+    let state = EmptyModifierState::from_nes_ram(&ram).unwrap();
+    assert!(state.gameplay_state.is_some());
+
+    ram[0xc0] = 5; // demo
+    assert_eq!(
+        EmptyModifierState::from_nes_ram(&ram).unwrap_err(),
+        SavestateError::UnsupportedGameMode { game_mode: 5 }
+    );
+}
+
+#[test]
+fn gameplay_state_to_nes_ram() {
+    let ram = get_gameplay_ram();
+    let gameplay_state = EmptyModifierGameplayState::from_nes_ram(&ram).unwrap();
+    let written_ram = gameplay_state.to_nes_ram();
+    assert_eq!(written_ram[0x73..0x76], [0x56, 0x34, 0x12]);
+    assert_eq!(written_ram[0x400..0x500], ram[0x400..0x500]);
+
+    let mut dumped_ram = ram;
+    dumped_ram[0x400 + 195] = 0x7d;
+    dumped_ram[0x400 + 199] = 0x7c;
+    gameplay_state.write_nes_ram(&mut dumped_ram);
+    assert_eq!(dumped_ram[0x400 + 195], 0x7d);
+    assert_eq!(dumped_ram[0x400 + 199], 0xef);
+}
+
+#[test]
+fn nes_ram_round_trip() {
+    for path in [
+        "tests/movies/death/normal_play.fm2",
+        "tests/movies/b_type_clear/tasvideos_920m.fm2",
+    ] {
+        let state = play_movie(&load_movie(path), |_, state| {
+            if let Some(gameplay_state) = &state.gameplay_state {
+                let ram = gameplay_state.to_nes_ram();
+                assert_eq!(&GameplayState::from_nes_ram(&ram).unwrap(), gameplay_state);
+            }
+        });
+
+        let ram = state.to_nes_ram();
+        assert_eq!(
+            EmptyModifierState::from_nes_ram(&ram).unwrap().high_scores,
+            state.high_scores
+        );
+    }
+}
+
+#[test]
+fn high_scores_nes_ram() {
+    let ram = EmptyModifierState::new().to_nes_ram();
+    assert_eq!(ram[0x700..0x706], [0x08, 0x0f, 0x17, 0x01, 0x12, 0x04]); // HOWARD
+    assert_eq!(ram[0x730..0x733], [0x01, 0x00, 0x00]); // 10000
+    assert_eq!(ram[0x748], 9);
+    assert_eq!(ram[0x718..0x71e], [0x01, 0x0c, 0x05, 0x18, 0x00, 0x00]); // ALEX
+    assert_eq!(ram[0x73c..0x73f], [0x00, 0x20, 0x00]); // 2000
+    assert_eq!(
+        EmptyModifierState::from_nes_ram(&ram).unwrap().high_scores,
+        HighScore::DEFAULT_TABLE
+    );
+
+    let mut state = EmptyModifierState::new();
+    state.high_scores[1][2] = HighScore::new(*b"A.B(1)", 123_456, 29);
+    let ram = state.to_nes_ram();
+    assert_eq!(ram[0x742..0x745], [0x12, 0x34, 0x56]);
+    assert_eq!(
+        EmptyModifierState::from_nes_ram(&ram).unwrap().high_scores,
+        state.high_scores
+    );
+
+    let mut ram = ram;
+    ram[0x701] = 43;
+    assert_eq!(
+        EmptyModifierState::from_nes_ram(&ram).unwrap_err(),
+        SavestateError::InvalidField {
+            field: "high_scores.name",
+            value: 43,
+        }
+    );
+}
+
+#[test]
+fn nes_ram_errors() {
+    let mut ram = get_gameplay_ram();
+    ram[0x74] = 0x3a;
+    assert_eq!(
+        EmptyModifierGameplayState::from_nes_ram(&ram).unwrap_err(),
+        SavestateError::InvalidField {
+            field: "score",
+            value: 0x3a,
+        }
+    );
+
+    let mut ram = get_gameplay_ram();
+    ram[0x17..0x19].fill(0);
+    assert!(matches!(
+        EmptyModifierGameplayState::from_nes_ram(&ram),
+        Err(SavestateError::InvalidField {
+            field: "random.index",
+            ..
+        })
+    ));
+
+    let mut ram = get_gameplay_ram();
+    ram[0x62] = 0x13;
+    assert!(matches!(
+        EmptyModifierGameplayState::from_nes_ram(&ram),
+        Err(SavestateError::InvalidField {
+            field: "current_piece",
+            ..
+        })
+    ));
+}
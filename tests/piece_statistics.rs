@@ -0,0 +1,49 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+mod common;
+
+use common::{load_movie, play_movie};
+use meta_nestris::{GameType, GameplayState, Input, Random};
+
+#[test]
+fn piece_statistics_count_spawned_pieces() {
+    let movie = load_movie("tests/movies/death/normal_play.fm2");
+
+    let mut spawned_pieces = 1;
+    let mut piece_counter = None;
+    play_movie(&movie, |_, state| {
+        if let Some(gameplay_state) = &state.gameplay_state {
+            // the RNG's piece counter advances once per spawned piece
+            if piece_counter.map_or(false, |c| c != gameplay_state.random.piece_counter) {
+                spawned_pieces += 1;
+            }
+            piece_counter = Some(gameplay_state.random.piece_counter);
+
+            let counted_pieces = gameplay_state.piece_statistics.iter().sum::<u16>();
+            assert_eq!(counted_pieces, spawned_pieces);
+        }
+    });
+    assert!(spawned_pieces > 1);
+}
+
+#[test]
+fn piece_statistics_wrap_around() {
+    let state = GameplayState::new(&Random::new(), 0, Input::empty(), GameType::A, 18, 0);
+    let first_type_index = state.current_piece.get_type_index().unwrap();
+    assert_eq!(state.piece_statistics.iter().sum::<u16>(), 1);
+    assert_eq!(state.piece_statistics[first_type_index], 1);
+
+    let next_type_index = state.next_piece.get_type_index().unwrap();
+    // the hundreds byte is not decimal adjusted, so 999 is followed by 1000
+    // and the statistic only wraps around after 25599
+    for (statistic, expected_statistic) in [(999, 1000), (25599, 0)] {
+        let mut state = state.clone();
+        state.piece_statistics[next_type_index] = statistic;
+        let piece_counter = state.random.piece_counter;
+        while state.random.piece_counter == piece_counter {
+            state.step(Input::empty());
+        }
+        assert_eq!(state.piece_statistics[next_type_index], expected_statistic);
+    }
+}
@@ -0,0 +1,270 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+mod common;
+
+use common::{load_movie, play_movie, EmptyModifierState};
+use meta_nestris::{GameplayState, MenuMode, Modifier, Movie, SavestateError};
+use std::fs;
+
+// a RAM trace holds, after every frame of a movie, the bytes of the NES'
+// internal RAM which are compared with the model, as recorded by
+// tests/movies/record_ram_trace.lua; both list the same address ranges
+const TRACED_RANGES: [(usize, usize); 9] = [
+    (0x17, 4),      // RNG seed, spawn ID and spawn count
+    (0x60, 0x1a),   // player 1 gameplay variables
+    (0xa7, 2),      // game mode state and general counter
+    (0xad, 1),      // level or height selection
+    (0xb1, 1),      // frame counter
+    (0xbd, 1),      // render mode
+    (0xbf, 5),      // next piece, game mode, game type, music type and sleep counter
+    (0xf7, 1),      // held buttons
+    (0x3f0, 0x110), // piece statistics and playfield
+];
+const FRAME_SIZE: usize = {
+    let mut size = 0;
+    let mut index = 0;
+    while index < TRACED_RANGES.len() {
+        size += TRACED_RANGES[index].1;
+        index += 1;
+    }
+    size
+};
+
+#[derive(Debug, Eq, PartialEq)]
+struct Divergence {
+    frame: usize,
+    field: &'static str,
+    emulator_value: String,
+    model_value: String,
+}
+
+// compares the listed fields in order, returning the first which differs
+macro_rules! first_mismatch {
+    ($emulator:expr, $model:expr, $($($field:ident).+),+ $(,)?) => {
+        'comparison: {
+            $(
+                if $emulator.$($field).+ != $model.$($field).+ {
+                    break 'comparison Some((
+                        stringify!($($field).+),
+                        format!("{:?}", $emulator.$($field).+),
+                        format!("{:?}", $model.$($field).+),
+                    ));
+                }
+            )+
+            None
+        }
+    };
+}
+
+#[test]
+#[ignore = "no RAM traces recorded in FCEUX are committed yet"]
+fn ram_traces_match_model() {
+    let mut trace_paths = Vec::new();
+    for category in fs::read_dir("tests/movies").unwrap() {
+        let category = category.unwrap().path();
+        if !category.is_dir() {
+            continue;
+        }
+
+        for file in fs::read_dir(category).unwrap() {
+            let path = file.unwrap().path();
+            if path.extension().map_or(false, |e| e == "ramtrace") {
+                trace_paths.push(path);
+            }
+        }
+    }
+
+    assert!(!trace_paths.is_empty(), "no RAM traces found");
+    for trace_path in trace_paths {
+        let movie = Movie::from_fm2(trace_path.with_extension("fm2")).unwrap();
+        let trace = fs::read(&trace_path).unwrap();
+
+        if let Some(divergence) = find_divergence(&movie, &trace) {
+            panic!(
+                "{}: model diverges at frame {}: {} is {} in the emulator, but {} in the model",
+                trace_path.display(),
+                divergence.frame,
+                divergence.field,
+                divergence.emulator_value,
+                divergence.model_value
+            );
+        }
+    }
+}
+
+// checks the harness itself against traces of the model, which says nothing
+// about the model's accuracy
+#[test]
+fn ram_trace_divergence() {
+    let movie = load_movie("tests/movies/death/normal_play.fm2");
+    let mut trace = record_model_trace(&movie);
+    assert_eq!(find_divergence(&movie, &trace), None);
+
+    let gameplay_frame = trace
+        .chunks_exact(FRAME_SIZE)
+        .position(|frame| expand_frame(frame)[0xc0] == 4)
+        .unwrap()
+        + 100;
+    // offsets of the score's middle byte and the piece's x position, which
+    // both lie in the second traced range
+    trace[gameplay_frame * FRAME_SIZE + 4 + 0x14] ^= 0x10;
+    trace[(gameplay_frame + 1) * FRAME_SIZE + 4] ^= 1;
+    let divergence = find_divergence(&movie, &trace).unwrap();
+    assert_eq!(divergence.frame, gameplay_frame + 1);
+    assert_eq!(divergence.field, "score");
+    assert_ne!(divergence.emulator_value, divergence.model_value);
+
+    trace.truncate(gameplay_frame * FRAME_SIZE);
+    assert_eq!(find_divergence(&movie, &trace), None);
+
+    // an unreadable menu frame is reported rather than skipped; the music
+    // type lies in the seventh traced range
+    let menu_frame = trace
+        .chunks_exact(FRAME_SIZE)
+        .rposition(|frame| expand_frame(frame)[0xc0] == 3)
+        .unwrap();
+    trace[menu_frame * FRAME_SIZE + 0x26] = 9;
+    let divergence = find_divergence(&movie, &trace).unwrap();
+    assert_eq!(divergence.frame, menu_frame + 1);
+    assert_eq!(divergence.field, "state");
+}
+
+// replays the movie for as many frames as the trace holds, returning the
+// first divergence from the trace; frames are numbered from 1
+fn find_divergence(movie: &Movie, trace: &[u8]) -> Option<Divergence> {
+    assert_eq!(trace.len() % FRAME_SIZE, 0, "truncated RAM trace");
+
+    let mut state = EmptyModifierState::new();
+    for (frame_index, frame) in trace.chunks_exact(FRAME_SIZE).enumerate() {
+        let ram = &expand_frame(frame);
+        state.apply_command(movie.commands.get(frame_index).copied().unwrap_or_default());
+        state.step(movie.inputs.get(frame_index).copied().unwrap_or_default());
+
+        if let Some((field, emulator_value, model_value)) = compare_state(ram, &state) {
+            return Some(Divergence {
+                frame: frame_index + 1,
+                field,
+                emulator_value,
+                model_value,
+            });
+        }
+    }
+
+    None
+}
+
+fn compare_state(
+    ram: &[u8; 0x800],
+    state: &EmptyModifierState,
+) -> Option<(&'static str, String, String)> {
+    // the progress of screen transitions is not held in RAM
+    if state.delay_timer > 0 {
+        return None;
+    }
+
+    let in_gameplay = ram[0xc0] == 4;
+    if in_gameplay != state.gameplay_state.is_some() {
+        return Some((
+            "gameplay_state",
+            describe_presence(in_gameplay),
+            describe_presence(state.gameplay_state.is_some()),
+        ));
+    }
+
+    if let Some(gameplay_state) = &state.gameplay_state {
+        return match GameplayState::from_nes_ram(ram) {
+            Ok(emulator_state) => compare_gameplay_state(&emulator_state, gameplay_state),
+            Err(error) => Some(("gameplay_state", error.to_string(), "valid".into())),
+        };
+    }
+
+    let emulator_state = match EmptyModifierState::from_nes_ram(ram) {
+        Ok(emulator_state) => emulator_state,
+        // the delay before the first input on the copyright screen is not
+        // held in RAM either, so such frames cannot be read
+        Err(SavestateError::InvalidField {
+            field: "sleep_counter",
+            ..
+        }) if state.menu_mode == MenuMode::CopyrightScreen => return None,
+        Err(error) => return Some(("state", error.to_string(), "valid".into())),
+    };
+    first_mismatch!(
+        emulator_state,
+        state,
+        menu_mode,
+        random,
+        frame_counter,
+        game_type,
+        selecting_height,
+        selected_level,
+        selected_height,
+        music_type,
+    )
+}
+
+fn compare_gameplay_state(
+    emulator_state: &GameplayState<{ Modifier::empty() }>,
+    state: &GameplayState<{ Modifier::empty() }>,
+) -> Option<(&'static str, String, String)> {
+    first_mismatch!(
+        emulator_state,
+        state,
+        dead,
+        paused,
+        game_mode_state,
+        play_state,
+        play_state_delay,
+        random.index,
+        random.piece_counter,
+        random.last_piece,
+        frame_counter,
+        current_piece,
+        next_piece,
+        current_piece_x,
+        current_piece_y,
+        fall_timer,
+        drop_autorepeat,
+        shift_autorepeat,
+        hold_down_points,
+        checked_row_offset,
+        update_lines_delay,
+        rendering_delay,
+        cleared_lines,
+        tiles,
+        score,
+        level,
+        line_count,
+        game_type,
+        piece_statistics,
+    )
+}
+
+fn describe_presence(present: bool) -> String {
+    if present { "present" } else { "absent" }.into()
+}
+
+// places the traced bytes of a frame at their addresses, leaving the rest of
+// the RAM image zeroed
+fn expand_frame(frame: &[u8]) -> [u8; 0x800] {
+    let mut ram = [0; 0x800];
+    let mut offset = 0;
+    for (address, length) in TRACED_RANGES {
+        ram[address..address + length].copy_from_slice(&frame[offset..offset + length]);
+        offset += length;
+    }
+
+    ram
+}
+
+fn record_model_trace(movie: &Movie) -> Vec<u8> {
+    let mut trace = Vec::new();
+    play_movie(movie, |_, state| {
+        let ram = state.to_nes_ram();
+        for (address, length) in TRACED_RANGES {
+            trace.extend(&ram[address..address + length]);
+        }
+    });
+
+    trace
+}
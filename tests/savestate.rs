@@ -0,0 +1,70 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+mod common;
+
+use common::{load_movie, play_movie, play_movie_file};
+use meta_nestris::{GameplayState, Modifier, SavestateError, State};
+
+#[test]
+fn savestate_round_trip() {
+    for path in [
+        "tests/movies/death/normal_play.fm2",
+        "tests/movies/b_type_clear/tasvideos_920m.fm2",
+    ] {
+        play_movie(&load_movie(path), |_, state| {
+            assert_eq!(&State::from_bytes(&state.to_bytes()).unwrap(), state);
+            if let Some(gameplay_state) = &state.gameplay_state {
+                let bytes = gameplay_state.to_bytes();
+                assert_eq!(&GameplayState::from_bytes(&bytes).unwrap(), gameplay_state);
+            }
+        });
+    }
+}
+
+#[test]
+fn savestate_errors() {
+    const UNCAPPED_SCORE_MODIFIER: Modifier = Modifier {
+        uncapped_score: true,
+        ..Modifier::empty()
+    };
+
+    let state = play_movie_file("tests/movies/menuing/gameplay.fm2");
+    let bytes = state.to_bytes();
+    let load = |bytes: &[u8]| State::<{ Modifier::empty() }>::from_bytes(bytes).unwrap_err();
+
+    assert_eq!(
+        load(&bytes[..bytes.len() - 1]),
+        SavestateError::UnexpectedEnd
+    );
+    assert_eq!(
+        load(&[&bytes[..], &[0]].concat()),
+        SavestateError::TrailingBytes { count: 1 }
+    );
+    assert_eq!(
+        load(&[&[0xff], &bytes[1..]].concat()),
+        SavestateError::UnsupportedVersion { version: 0xff }
+    );
+    assert_eq!(
+        GameplayState::<{ Modifier::empty() }>::from_bytes(&bytes).unwrap_err(),
+        SavestateError::WrongStateType
+    );
+    assert_eq!(
+        State::<UNCAPPED_SCORE_MODIFIER>::from_bytes(&bytes).unwrap_err(),
+        SavestateError::ModifierMismatch {
+            expected: UNCAPPED_SCORE_MODIFIER,
+            found: Modifier::empty(),
+        }
+    );
+
+    // selected_height follows a 3-byte header and 14 bytes of other fields
+    let mut invalid_bytes = bytes;
+    invalid_bytes[17] = 6;
+    assert_eq!(
+        load(&invalid_bytes),
+        SavestateError::InvalidField {
+            field: "selected_height",
+            value: 6,
+        }
+    );
+}
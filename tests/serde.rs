@@ -0,0 +1,27 @@
+#![cfg(feature = "serde")]
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+mod common;
+
+use common::{load_movie, play_movie};
+
+#[test]
+fn serde_round_trip() {
+    let movie = load_movie("tests/movies/death/normal_play.fm2");
+
+    let state = play_movie(&movie, |frame_index, state| {
+        if frame_index % 500 == 0 {
+            let yaml = serde_yaml::to_string(state).unwrap();
+            let deserialized_state = serde_yaml::from_str(&yaml).unwrap();
+            assert_eq!(*state, deserialized_state);
+        }
+    });
+
+    let gameplay_state = state.gameplay_state.unwrap();
+    let yaml = serde_yaml::to_string(&gameplay_state).unwrap();
+    assert!(yaml.contains("tiles:"));
+    assert!(yaml.contains("line_count:"));
+    let deserialized_gameplay_state = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(gameplay_state, deserialized_gameplay_state);
+}